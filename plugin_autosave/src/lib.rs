@@ -0,0 +1,479 @@
+use std::future::Future;
+
+use geo::Coordinate;
+use gloo::{events::EventListener, timers::callback::Timeout};
+use rust_editor::{
+    input::{keyboard::Key, mouse},
+    log,
+    plugin::{Plugin, PluginWithOptions},
+    store::{self, StorageBackend, Store, StoreError, TaskQueue},
+    ui::{app::EditorError, dialog::Dialog},
+};
+use rust_macro::editor_plugin;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+pub enum AutosaveMessage<Data> {
+    /// The data was changed through the history
+    Changed,
+
+    /// The debounce timer elapsed or the page is hidden
+    Save,
+
+    /// The snapshots of a previous session were read on startup
+    Found(RecoveryIndex),
+
+    /// Loads the snapshot with the given sequence number
+    Restore(u64),
+
+    /// The data of a snapshot was loaded
+    Restored(Data),
+
+    /// Deletes the snapshots of the previous session
+    Discard,
+}
+
+/// Describes a saved recovery snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    pub sequence: u64,
+
+    /// Milliseconds since the unix epoch
+    pub saved_at: f64,
+}
+
+/// Lists all recovery snapshots from the oldest to the newest one.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RecoveryIndex {
+    pub snapshots: Vec<SnapshotInfo>,
+}
+
+struct Storage<Data> {
+    name: String,
+    backend: Rc<dyn StorageBackend>,
+
+    serialize: fn(&Data) -> Result<Value, StoreError>,
+    deserialize: fn(Value) -> Result<Data, StoreError>,
+}
+
+impl<Data> Storage<Data> {
+    fn index(&self) -> Store {
+        Store::with_backend(&format!("{}.recovery", self.name), Rc::clone(&self.backend))
+    }
+
+    fn snapshot(&self, sequence: u64) -> Store {
+        Store::with_backend(
+            &format!("{}.recovery.{}", self.name, sequence),
+            Rc::clone(&self.backend),
+        )
+    }
+}
+
+/// Saves the data of the editor as recovery snapshots, so that work is not lost if the browser crashes or the page is
+/// closed without saving.
+///
+/// A snapshot is saved a few seconds after the last change, whether it was made through the history or directly by a
+/// mouse or keyboard interaction, and when the page is hidden. Nothing is saved if the data equals the latest snapshot.
+/// Only the latest snapshots are kept. If snapshots are found on startup, a dialog offers to restore one of them.
+///
+/// Storage backends are asynchronous, so the save that starts when the page is hidden may not finish if the page is
+/// closed right away. Changes of the last seconds before closing the page can therefore be lost.
+///
+/// The snapshots are deleted with [Autosave::mark_saved], which the host application should call after the user saved
+/// the document.
+///
+/// # Example
+///
+/// ```
+/// let mut autosave = Autosave::<Map>::default();
+/// autosave.set_storage("map", Rc::new(IndexedDbBackend::new("editor", "recovery")));
+///
+/// editor.add_plugin(autosave);
+/// ```
+#[editor_plugin]
+pub struct Autosave<Data> {
+    #[option(
+        default = 5,
+        min = 1,
+        max = 600,
+        label = "Interval",
+        description = "Seconds without changes before a snapshot is saved"
+    )]
+    interval: u32,
+
+    #[option(
+        default = 3,
+        min = 1,
+        max = 20,
+        label = "Snapshots",
+        description = "Number of recovery snapshots that are kept"
+    )]
+    snapshots: u8,
+
+    #[option(skip)]
+    storage: Option<Rc<Storage<Data>>>,
+
+    #[option(skip)]
+    index: RecoveryIndex,
+
+    /// Sequence number of the latest snapshot, including the ones of a previous session
+    #[option(skip)]
+    sequence: u64,
+
+    /// Snapshots of a previous session the user has not decided about yet
+    #[option(skip)]
+    recovery: Option<RecoveryIndex>,
+
+    /// True once the snapshots of a previous session were handled. Nothing is saved before that, otherwise they would
+    /// be overwritten.
+    #[option(skip)]
+    ready: bool,
+
+    /// True if the data may have changed since the latest snapshot
+    #[option(skip)]
+    dirty: bool,
+
+    /// Data of the latest snapshot or the data that was saved by the host application
+    #[option(skip)]
+    saved: Option<Value>,
+
+    #[option(skip)]
+    sender: Option<Callback<EditorMessages<Data>>>,
+
+    #[option(skip)]
+    timer: Option<Timeout>,
+
+    #[option(skip)]
+    unload_listener: Option<EventListener>,
+
+    #[option(skip)]
+    queue: TaskQueue,
+}
+
+impl<Data> Autosave<Data>
+where
+    Data: Default + 'static,
+{
+    /// Saves the snapshots under `name` in `backend`. Needs to be called before the plugin is added to the editor,
+    /// nothing is saved otherwise.
+    pub fn set_storage(&mut self, name: &str, backend: Rc<dyn StorageBackend>)
+    where
+        Data: Serialize + DeserializeOwned,
+    {
+        self.storage = Some(Rc::new(Storage {
+            name: String::from(name),
+            backend,
+            serialize: |data| {
+                serde_json::to_value(data).map_err(|e| StoreError::Serialization(Box::new(e)))
+            },
+            deserialize: store::from_value,
+        }));
+    }
+
+    /// Deletes all snapshots. Call it with the saved data after the document was saved, so that the user is not asked
+    /// to restore it on the next start.
+    pub fn mark_saved(&mut self, data: &Data) {
+        self.dirty = false;
+        self.timer = None;
+        self.saved = self
+            .storage
+            .as_ref()
+            .and_then(|storage| (storage.serialize)(data).ok());
+
+        let index = std::mem::take(&mut self.index);
+        self.delete(index);
+    }
+
+    fn report(sender: &Option<Callback<EditorMessages<Data>>>, error: StoreError) {
+        match sender {
+            Some(sender) => sender.emit(EditorMessages::StoreFailed(error)),
+            None => log!("autosave failed: {}", error),
+        }
+    }
+
+    /// Runs `task` after all previously spawned tasks and reports it to the editor if it fails, see
+    /// [EditorMessages::StoreFailed].
+    fn spawn(&self, task: impl Future<Output = Result<(), StoreError>> + 'static) {
+        let sender = self.sender.clone();
+        self.queue.spawn(async move {
+            if let Err(e) = task.await {
+                Self::report(&sender, e);
+            }
+        });
+    }
+
+    fn load_index(&self) {
+        let (storage, sender) = match (&self.storage, &self.sender) {
+            (Some(storage), Some(sender)) => (Rc::clone(storage), sender.clone()),
+            _ => return,
+        };
+
+        self.spawn(async move {
+            let index = storage.index().fetch::<RecoveryIndex>().await?;
+
+            sender.emit(EditorMessages::PluginMessage(
+                Autosave::<Data>::identifier(),
+                Box::new(AutosaveMessage::<Data>::Found(index.unwrap_or_default())),
+            ));
+
+            Ok(())
+        });
+    }
+
+    fn restart_timer(&mut self) {
+        let sender = match &self.sender {
+            Some(sender) => sender.clone(),
+            None => return,
+        };
+
+        // Replacing the timer cancels the previous one
+        self.timer = Some(Timeout::new(self.interval * 1000, move || {
+            sender.emit(EditorMessages::PluginMessage(
+                Autosave::<Data>::identifier(),
+                Box::new(AutosaveMessage::<Data>::Save),
+            ))
+        }));
+    }
+
+    fn changed(&mut self) {
+        self.dirty = true;
+        self.restart_timer();
+    }
+
+    fn save(&mut self, data: &Data) {
+        self.timer = None;
+
+        if !self.dirty || !self.ready {
+            return;
+        }
+
+        let storage = match &self.storage {
+            Some(storage) => Rc::clone(storage),
+            None => return,
+        };
+
+        let value = match (storage.serialize)(data) {
+            Ok(value) => value,
+            Err(e) => {
+                Self::report(&self.sender, e);
+                return;
+            }
+        };
+        self.dirty = false;
+
+        if self.saved.as_ref() == Some(&value) {
+            return;
+        }
+        self.saved = Some(value.clone());
+
+        self.sequence += 1;
+        let sequence = self.sequence;
+        self.index.snapshots.push(SnapshotInfo {
+            sequence,
+            saved_at: js_sys::Date::now(),
+        });
+
+        let overflow = self
+            .index
+            .snapshots
+            .len()
+            .saturating_sub(self.snapshots.max(1) as usize);
+        let removed: Vec<SnapshotInfo> = self.index.snapshots.drain(..overflow).collect();
+
+        let index = self.index.clone();
+        self.spawn(async move {
+            // The index is written after the snapshot, so it never lists a snapshot that does not exist
+            storage.snapshot(sequence).sync(&value).await?;
+            storage.index().sync(&index).await?;
+
+            for snapshot in removed {
+                storage.snapshot(snapshot.sequence).remove().await?;
+            }
+
+            Ok(())
+        });
+    }
+
+    fn restore(&self, sequence: u64) {
+        let (storage, sender) = match (&self.storage, &self.sender) {
+            (Some(storage), Some(sender)) => (Rc::clone(storage), sender.clone()),
+            _ => return,
+        };
+
+        self.spawn(async move {
+            let value = storage
+                .snapshot(sequence)
+                .fetch::<Value>()
+                .await?
+                .ok_or_else(|| StoreError::Backend(format!("snapshot {} is missing", sequence)))?;
+            let data = (storage.deserialize)(value)?;
+
+            sender.emit(EditorMessages::PluginMessage(
+                Autosave::<Data>::identifier(),
+                Box::new(AutosaveMessage::Restored(data)),
+            ));
+
+            Ok(())
+        });
+    }
+
+    /// Deletes all snapshots listed in `index` together with the index itself.
+    fn delete(&self, index: RecoveryIndex) {
+        let storage = match &self.storage {
+            Some(storage) => Rc::clone(storage),
+            None => return,
+        };
+
+        self.spawn(async move {
+            storage.index().remove().await?;
+
+            for snapshot in index.snapshots {
+                storage.snapshot(snapshot.sequence).remove().await?;
+            }
+
+            Ok(())
+        });
+    }
+
+    fn view_recovery(&self, recovery: &RecoveryIndex, ctx: &Context<App<Data>>) -> Html {
+        let restore = |sequence: u64| {
+            ctx.link().callback(move |_| {
+                EditorMessages::PluginMessage(
+                    Autosave::<Data>::identifier(),
+                    Box::new(AutosaveMessage::<Data>::Restore(sequence)),
+                )
+            })
+        };
+
+        let discard = ctx.link().callback(|_| {
+            EditorMessages::PluginMessage(
+                Autosave::<Data>::identifier(),
+                Box::new(AutosaveMessage::<Data>::Discard),
+            )
+        });
+
+        html! {
+            <Dialog title="Restore unsaved changes?">
+                <ul class="recovery_snapshots">
+                {
+                    for recovery.snapshots.iter().rev().map(|snapshot| {
+                        let saved_at = js_sys::Date::new(&JsValue::from_f64(snapshot.saved_at))
+                            .to_locale_string("default", &JsValue::UNDEFINED);
+
+                        html! {
+                            <li>
+                                <button onclick={restore(snapshot.sequence)}>
+                                    {format!("Restore {}", String::from(saved_at))}
+                                </button>
+                            </li>
+                        }
+                    })
+                }
+                </ul>
+                <button onclick={discard}>{"Discard"}</button>
+            </Dialog>
+        }
+    }
+}
+
+impl<Data> Plugin<Data> for Autosave<Data>
+where
+    Data: Default + 'static,
+{
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        if self.storage.is_none() {
+            log!("autosave is disabled, call Autosave::set_storage before adding the plugin");
+            return Ok(());
+        }
+
+        self.sender = editor.message_callback();
+
+        if let Some(sender) = self.sender.clone() {
+            editor.history_mut().listen(move |_| {
+                sender.emit(EditorMessages::PluginMessage(
+                    Autosave::<Data>::identifier(),
+                    Box::new(AutosaveMessage::<Data>::Changed),
+                ))
+            });
+        }
+
+        if let (Some(window), Some(sender)) = (web_sys::window(), self.sender.clone()) {
+            // pagehide is also fired when the page is put into the back/forward cache, where beforeunload is not. The
+            // save is only started, the browser may close the page before it finished.
+            self.unload_listener = Some(EventListener::new(&window, "pagehide", move |_| {
+                sender.emit(EditorMessages::PluginMessage(
+                    Autosave::<Data>::identifier(),
+                    Box::new(AutosaveMessage::<Data>::Save),
+                ))
+            }));
+        }
+
+        self.load_index();
+
+        Ok(())
+    }
+
+    // Changes made directly to the data are not reported through the history, so every interaction that might have
+    // changed it restarts the timer
+    fn mouse_up(&mut self, _: Coordinate<f64>, _: mouse::Button, _: &mut App<Data>) -> bool {
+        self.changed();
+        false
+    }
+
+    fn key_up(&mut self, _: Key, _: &mut App<Data>) {
+        self.changed();
+    }
+
+    fn editor_elements(&mut self, ctx: &Context<App<Data>>, _: &App<Data>) -> Vec<Html> {
+        match &self.recovery {
+            Some(recovery) => vec![self.view_recovery(recovery, ctx)],
+            None => Vec::default(),
+        }
+    }
+
+    fn on_message_with_editor(&mut self, message: Box<dyn Any>, editor: &mut App<Data>) {
+        let message = match message.downcast::<AutosaveMessage<Data>>() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        match *message {
+            AutosaveMessage::Changed => self.changed(),
+            AutosaveMessage::Save => self.save(editor.data()),
+            AutosaveMessage::Found(index) => {
+                if let Some(latest) = index.snapshots.last() {
+                    self.sequence = self.sequence.max(latest.sequence);
+                }
+
+                if index.snapshots.is_empty() {
+                    self.ready = true;
+                    self.save(editor.data());
+                } else {
+                    self.recovery = Some(index);
+                }
+            }
+            AutosaveMessage::Restore(sequence) => self.restore(sequence),
+            AutosaveMessage::Restored(data) => {
+                editor.set_data(data);
+                editor.history_mut().clear();
+
+                // The restored snapshots are kept until the next ones replace them
+                self.index = self.recovery.take().unwrap_or_default();
+                self.ready = true;
+                self.dirty = false;
+                self.saved = self
+                    .storage
+                    .as_ref()
+                    .and_then(|storage| (storage.serialize)(editor.data()).ok());
+            }
+            AutosaveMessage::Discard => {
+                let recovery = self.recovery.take().unwrap_or_default();
+                self.delete(recovery);
+
+                self.ready = true;
+                self.save(editor.data());
+            }
+        }
+    }
+}
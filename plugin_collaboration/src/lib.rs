@@ -0,0 +1,164 @@
+use rust_editor::{
+    actions::ActionRegistry,
+    error,
+    history::HistoryEvent,
+    plugin::{Plugin, PluginWithOptions},
+    ui::app::EditorError,
+};
+use rust_macro::editor_plugin;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub mod log;
+pub mod operation;
+pub mod transport;
+pub mod websocket;
+
+use self::log::OperationLog;
+use self::operation::{Operation, OperationId};
+use self::transport::Transport;
+
+#[derive(Error, Debug)]
+pub enum CollaborationError {
+    #[error("operation {0} refers to an operation that was not received")]
+    UnknownOperation(OperationId),
+
+    #[error("could not (de)serialize operation: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("transport failed: {0}")]
+    Transport(String),
+}
+
+pub enum CollaborationMessage {
+    /// An operation of another site arrived
+    Received(Operation),
+}
+
+struct Session<Data> {
+    log: OperationLog<Data>,
+    transport: Box<dyn Transport>,
+    registry: ActionRegistry<Data>,
+}
+
+/// Lets several users edit the same data at the same time.
+///
+/// All actions that are executed, undone or redone through the history are sent to the other sites and their changes
+/// are applied locally, see [OperationLog] for how they are merged. Changes of other users are not added to the local
+/// history and are therefore never undone by it, also not when undoing a local action that was executed before them.
+/// They are applied in between local transactions and gestures, see [App::apply_external].
+///
+/// Only serializable actions are shared, and all sites have to start with the same data.
+///
+/// # Example
+///
+/// ```
+/// editor.plugin_mut(|collaboration: &mut Collaboration<Map>| {
+///     let transport = WebSocketTransport::connect("wss://example.com/session/42").unwrap();
+///     collaboration.connect(transport, registry());
+/// });
+/// ```
+#[editor_plugin(skip)]
+pub struct Collaboration<Data> {
+    #[option(skip)]
+    session: Rc<RefCell<Option<Session<Data>>>>,
+
+    #[option(skip)]
+    sender: Option<Callback<EditorMessages<Data>>>,
+}
+
+impl<Data> Collaboration<Data>
+where
+    Data: Default + 'static,
+{
+    /// Starts sharing changes through `transport`. Received actions are restored with `registry`, so it needs to
+    /// contain every action any of the sites might execute.
+    pub fn connect(&mut self, transport: impl Transport + 'static, registry: ActionRegistry<Data>) {
+        let mut transport: Box<dyn Transport> = Box::new(transport);
+        if let Some(sender) = &self.sender {
+            Self::attach(transport.as_mut(), sender.clone());
+        }
+
+        *self.session.borrow_mut() = Some(Session {
+            log: OperationLog::new(Uuid::new_v4()),
+            transport,
+            registry,
+        });
+    }
+
+    /// Stops sharing changes.
+    pub fn disconnect(&mut self) {
+        *self.session.borrow_mut() = None;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.session.as_ref().borrow().is_some()
+    }
+
+    fn attach(transport: &mut dyn Transport, sender: Callback<EditorMessages<Data>>) {
+        transport.set_receiver(Box::new(move |operation| {
+            sender.emit(EditorMessages::PluginMessage(
+                Collaboration::<Data>::identifier(),
+                Box::new(CollaborationMessage::Received(operation)),
+            ))
+        }));
+    }
+}
+
+impl<Data> Plugin<Data> for Collaboration<Data>
+where
+    Data: Default + 'static,
+{
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        self.sender = editor.message_callback();
+
+        if let (Some(session), Some(sender)) = (self.session.borrow_mut().as_mut(), &self.sender) {
+            Self::attach(session.transport.as_mut(), sender.clone());
+        }
+
+        let session = Rc::clone(&self.session);
+        editor.history_mut().set_applier(move |event, data| {
+            match session.as_ref().borrow_mut().as_mut() {
+                Some(session) => session.log.apply(event, data),
+                None => match event {
+                    HistoryEvent::Undone(action) => action.borrow_mut().undo(data),
+                    HistoryEvent::Redone(action) => action.borrow_mut().redo(data),
+                    HistoryEvent::Executed(_) => {}
+                },
+            }
+        });
+
+        let session = Rc::clone(&self.session);
+        editor.history_mut().listen(move |event| {
+            if let Some(session) = session.as_ref().borrow_mut().as_mut() {
+                if let Some(operation) = session.log.record(event) {
+                    if let Err(e) = session.transport.send(&operation) {
+                        error!("{}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn on_message_with_editor(&mut self, message: Box<dyn Any>, editor: &mut App<Data>) {
+        let message = match message.downcast::<CollaborationMessage>() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        match *message {
+            CollaborationMessage::Received(operation) => {
+                let session = Rc::clone(&self.session);
+                editor.apply_external(move |data| {
+                    if let Some(session) = session.as_ref().borrow_mut().as_mut() {
+                        if let Err(e) = session.log.receive(operation, data, &session.registry) {
+                            error!("{}", e);
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
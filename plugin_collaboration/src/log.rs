@@ -0,0 +1,475 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use rust_editor::{
+    actions::{Action, ActionRegistry},
+    history::HistoryEvent,
+};
+
+use crate::{
+    operation::{Operation, OperationId, OperationKind, SiteId},
+    CollaborationError,
+};
+
+struct Entry<Data> {
+    operation: Operation,
+
+    /// The executed action or `None` if the operation undoes or redoes the action of another entry
+    action: Option<Rc<RefCell<dyn Action<Data>>>>,
+
+    /// False if the action was undone
+    applied: bool,
+}
+
+impl<Data> Entry<Data> {
+    fn apply(&self, data: &mut Data) {
+        if let (Some(action), true) = (&self.action, self.applied) {
+            action.borrow_mut().redo(data);
+        }
+    }
+
+    fn revert(&self, data: &mut Data) {
+        if let (Some(action), true) = (&self.action, self.applied) {
+            action.borrow_mut().undo(data);
+        }
+    }
+}
+
+/// Keeps all operations of a collaboration session in the same order on every site.
+///
+/// Operations are ordered by their Lamport timestamp and site. If a remote operation belongs before operations that were
+/// already applied, those are reverted, the remote operation is applied and the reverted ones are applied again. That
+/// way all sites end up with the same data no matter in which order they receive the operations, as long as they started
+/// with the same data and all actions are deterministic.
+///
+/// Local actions are shared as they are executed, undone and redone through the history. Undoing an action is selective:
+/// all later operations are reverted, the action is undone and the later operations are applied again, so changes of
+/// other sites made in the meantime are kept. Use [OperationLog::apply] to undo and redo local actions that way.
+pub struct OperationLog<Data> {
+    site: SiteId,
+
+    /// Lamport clock, the highest timestamp seen so far
+    clock: u64,
+
+    /// Number of integrated operations for each site
+    version: BTreeMap<SiteId, u64>,
+
+    entries: Vec<Entry<Data>>,
+
+    /// Received operations that depend on operations not integrated yet
+    pending: Vec<Operation>,
+}
+
+impl<Data> OperationLog<Data>
+where
+    Data: 'static,
+{
+    pub fn new(site: SiteId) -> Self {
+        OperationLog {
+            site,
+            clock: 0,
+            version: BTreeMap::new(),
+            entries: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn site(&self) -> SiteId {
+        self.site
+    }
+
+    /// Returns the number of integrated operations.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records a change that was made locally and is already applied to the data. Returns the operation that needs to
+    /// be sent to the other sites or `None` if the change cannot be shared because the action is not serializable.
+    pub fn record(&mut self, event: &HistoryEvent<Data>) -> Option<Operation> {
+        let (kind, action) = match event {
+            HistoryEvent::Executed(action) => (
+                OperationKind::Execute(action.borrow().to_serialized()?),
+                Some(Rc::clone(action)),
+            ),
+            HistoryEvent::Undone(action) => (OperationKind::Undo(self.find(action)?.0), None),
+            HistoryEvent::Redone(action) => (OperationKind::Redo(self.find(action)?.0), None),
+        };
+
+        let sequence = self.version.get(&self.site).copied().unwrap_or(0) + 1;
+        self.clock += 1;
+
+        let operation = Operation {
+            id: OperationId {
+                site: self.site,
+                sequence,
+            },
+            timestamp: self.clock,
+            dependencies: self.version.clone(),
+            kind,
+        };
+        self.version.insert(self.site, sequence);
+
+        // The timestamp is higher than the ones of all known operations, so the operation belongs to the end of the log
+        self.entries.push(Entry {
+            operation: operation.clone(),
+            action,
+            applied: true,
+        });
+
+        Some(operation)
+    }
+
+    /// Undoes or redoes a local action as described by `event` without reverting the operations that followed it.
+    /// Meant to be used as the [applier](rust_editor::history::History::set_applier) of the history, the event still
+    /// needs to be [recorded](OperationLog::record) afterwards. Actions that are not part of the log are undone and
+    /// redone directly.
+    pub fn apply(&mut self, event: &HistoryEvent<Data>, data: &mut Data) {
+        let (action, applied) = match event {
+            HistoryEvent::Executed(_) => return,
+            HistoryEvent::Undone(action) => (action, false),
+            HistoryEvent::Redone(action) => (action, true),
+        };
+
+        match self.find(action) {
+            Some((_, index)) => self.set_applied(index, applied, data),
+            None if applied => action.borrow_mut().redo(data),
+            None => action.borrow_mut().undo(data),
+        }
+    }
+
+    /// Integrates an operation received from another site into the log and applies it to the data. Operations that
+    /// were already integrated are ignored, operations that depend on missing ones are kept until those arrive.
+    ///
+    /// # Errors
+    ///
+    /// Operations that cannot be integrated, e.g. because their action is not registered, are skipped so that the
+    /// following operations of their site are not blocked. The first error is returned once all other operations that
+    /// became ready are integrated.
+    pub fn receive(
+        &mut self,
+        operation: Operation,
+        data: &mut Data,
+        registry: &ActionRegistry<Data>,
+    ) -> Result<(), CollaborationError> {
+        if self.is_integrated(&operation.id)
+            || self
+                .pending
+                .iter()
+                .any(|pending| pending.id == operation.id)
+        {
+            return Ok(());
+        }
+
+        self.pending.push(operation);
+
+        let mut result = Ok(());
+        while let Some(index) = self
+            .pending
+            .iter()
+            .position(|operation| self.is_ready(operation))
+        {
+            let operation = self.pending.remove(index);
+            let (id, timestamp) = (operation.id, operation.timestamp);
+
+            if let Err(error) = self.integrate(operation, data, registry) {
+                self.clock = self.clock.max(timestamp);
+                self.version.insert(id.site, id.sequence);
+                result = result.and(Err(error));
+            }
+        }
+
+        result
+    }
+
+    fn is_integrated(&self, id: &OperationId) -> bool {
+        self.version.get(&id.site).copied().unwrap_or(0) >= id.sequence
+    }
+
+    fn is_ready(&self, operation: &Operation) -> bool {
+        let integrated = self.version.get(&operation.id.site).copied().unwrap_or(0);
+
+        integrated + 1 == operation.id.sequence
+            && operation
+                .dependencies
+                .iter()
+                .all(|(site, count)| self.version.get(site).copied().unwrap_or(0) >= *count)
+    }
+
+    fn integrate(
+        &mut self,
+        operation: Operation,
+        data: &mut Data,
+        registry: &ActionRegistry<Data>,
+    ) -> Result<(), CollaborationError> {
+        let action: Option<Rc<RefCell<dyn Action<Data>>>> = match &operation.kind {
+            OperationKind::Execute(serialized) => Some(Rc::new(RefCell::new(
+                registry.deserialize(serialized.clone())?,
+            ))),
+            OperationKind::Undo(id) => {
+                let index = self.index_of(id)?;
+                self.set_applied(index, false, data);
+                None
+            }
+            OperationKind::Redo(id) => {
+                let index = self.index_of(id)?;
+                self.set_applied(index, true, data);
+                None
+            }
+        };
+
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.operation.order() > operation.order())
+            .unwrap_or(self.entries.len());
+
+        let entry = Entry {
+            action,
+            applied: true,
+            operation,
+        };
+
+        // Undoing and redoing changes the data at the position of the undone action, so only executed actions have to be
+        // placed between the operations that follow them
+        if entry.action.is_some() {
+            for entry in self.entries[position..].iter().rev() {
+                entry.revert(data);
+            }
+
+            entry.apply(data);
+
+            for entry in self.entries[position..].iter() {
+                entry.apply(data);
+            }
+        }
+
+        self.clock = self.clock.max(entry.operation.timestamp);
+        self.version
+            .insert(entry.operation.id.site, entry.operation.id.sequence);
+        self.entries.insert(position, entry);
+
+        Ok(())
+    }
+
+    /// Undoes or redoes the action of the entry at `index` at its position in the log. The following entries are
+    /// reverted before and applied again afterwards.
+    fn set_applied(&mut self, index: usize, applied: bool, data: &mut Data) {
+        if self.entries[index].applied == applied {
+            return;
+        }
+
+        for entry in self.entries[index + 1..].iter().rev() {
+            entry.revert(data);
+        }
+
+        let entry = &mut self.entries[index];
+        if let Some(action) = &entry.action {
+            match applied {
+                true => action.borrow_mut().redo(data),
+                false => action.borrow_mut().undo(data),
+            }
+        }
+        entry.applied = applied;
+
+        for entry in self.entries[index + 1..].iter() {
+            entry.apply(data);
+        }
+    }
+
+    /// Returns the id and index of the operation that executed `action`.
+    fn find(&self, action: &Rc<RefCell<dyn Action<Data>>>) -> Option<(OperationId, usize)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, entry)| {
+                entry.action.as_ref().is_some_and(|executed| {
+                    Rc::as_ptr(executed) as *const () == Rc::as_ptr(action) as *const ()
+                })
+            })
+            .map(|(index, entry)| (entry.operation.id, index))
+    }
+
+    /// Returns the index of the operation with `id` that executed an action.
+    fn index_of(&self, id: &OperationId) -> Result<usize, CollaborationError> {
+        self.entries
+            .iter()
+            .position(|entry| entry.operation.id == *id && entry.action.is_some())
+            .ok_or(CollaborationError::UnknownOperation(*id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fmt, rc::Rc};
+
+    use rust_editor::{
+        actions::{Action, ActionRegistry, Redo, SerializedAction, TaggedAction, Undo},
+        history::HistoryEvent,
+    };
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use super::OperationLog;
+    use crate::operation::OperationKind;
+
+    /// Sets the data to a value, which does not commute with other actions
+    #[derive(Serialize, Deserialize)]
+    struct Set {
+        value: i32,
+        previous: i32,
+    }
+
+    impl fmt::Display for Set {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Set {}", self.value)
+        }
+    }
+
+    impl Undo<i32> for Set {
+        fn undo(&mut self, data: &mut i32) {
+            *data = self.previous;
+        }
+    }
+
+    impl Redo<i32> for Set {
+        fn redo(&mut self, data: &mut i32) {
+            self.previous = *data;
+            *data = self.value;
+        }
+    }
+
+    impl TaggedAction for Set {
+        const TAG: &'static str = "set";
+    }
+
+    impl Action<i32> for Set {
+        fn to_serialized(&self) -> Option<SerializedAction> {
+            SerializedAction::new(self)
+        }
+    }
+
+    fn execute(
+        log: &mut OperationLog<i32>,
+        data: &mut i32,
+        value: i32,
+    ) -> Rc<RefCell<dyn Action<i32>>> {
+        let action: Rc<RefCell<dyn Action<i32>>> =
+            Rc::new(RefCell::new(Set { value, previous: 0 }));
+        action.borrow_mut().execute(data);
+        log.record(&HistoryEvent::Executed(&action));
+
+        action
+    }
+
+    #[test]
+    fn concurrent_operations_converge() {
+        let mut registry = ActionRegistry::new();
+        registry.register::<Set>();
+
+        let (mut a, mut data_a) = (OperationLog::new(Uuid::new_v4()), 0);
+        let (mut b, mut data_b) = (OperationLog::new(Uuid::new_v4()), 0);
+
+        execute(&mut a, &mut data_a, 1);
+        execute(&mut b, &mut data_b, 2);
+
+        let from_a = a.entries[0].operation.clone();
+        let from_b = b.entries[0].operation.clone();
+        a.receive(from_b.clone(), &mut data_a, &registry).unwrap();
+        b.receive(from_a, &mut data_b, &registry).unwrap();
+
+        assert_eq!(data_a, data_b);
+
+        // Receiving an operation twice has no effect
+        a.receive(from_b, &mut data_a, &registry).unwrap();
+        assert_eq!(a.len(), 2);
+    }
+
+    #[test]
+    fn undo_keeps_remote_changes() {
+        let mut registry = ActionRegistry::new();
+        registry.register::<Set>();
+
+        let (mut a, mut data_a) = (OperationLog::new(Uuid::new_v4()), 0);
+        let (mut b, mut data_b) = (OperationLog::new(Uuid::new_v4()), 0);
+
+        let action = execute(&mut a, &mut data_a, 1);
+        b.receive(a.entries[0].operation.clone(), &mut data_b, &registry)
+            .unwrap();
+
+        execute(&mut b, &mut data_b, 2);
+        a.receive(b.entries[1].operation.clone(), &mut data_a, &registry)
+            .unwrap();
+        assert_eq!(data_a, 2);
+
+        // Undoing the first action of a does not overwrite the later change of b
+        a.apply(&HistoryEvent::Undone(&action), &mut data_a);
+        let undo = a.record(&HistoryEvent::Undone(&action)).unwrap();
+        b.receive(undo, &mut data_b, &registry).unwrap();
+
+        assert_eq!(data_a, 2);
+        assert_eq!(data_b, 2);
+        assert_eq!(b.len(), 3);
+
+        // Redoing it does not either, and b reverts to the value of a when undoing its change
+        a.apply(&HistoryEvent::Redone(&action), &mut data_a);
+        let redo = a.record(&HistoryEvent::Redone(&action)).unwrap();
+        b.receive(redo, &mut data_b, &registry).unwrap();
+        assert_eq!((data_a, data_b), (2, 2));
+
+        let change_of_b = Rc::clone(b.entries[1].action.as_ref().unwrap());
+        b.apply(&HistoryEvent::Undone(&change_of_b), &mut data_b);
+        let undo = b.record(&HistoryEvent::Undone(&change_of_b)).unwrap();
+        a.receive(undo, &mut data_a, &registry).unwrap();
+        assert_eq!((data_a, data_b), (1, 1));
+    }
+
+    #[test]
+    fn waits_for_missing_operations() {
+        let mut registry = ActionRegistry::new();
+        registry.register::<Set>();
+
+        let (mut a, mut data_a) = (OperationLog::new(Uuid::new_v4()), 0);
+        let (mut b, mut data_b) = (OperationLog::new(Uuid::new_v4()), 0);
+
+        execute(&mut a, &mut data_a, 1);
+        execute(&mut a, &mut data_a, 2);
+
+        b.receive(a.entries[1].operation.clone(), &mut data_b, &registry)
+            .unwrap();
+        assert_eq!(b.len(), 0);
+
+        b.receive(a.entries[0].operation.clone(), &mut data_b, &registry)
+            .unwrap();
+        assert_eq!(b.len(), 2);
+        assert_eq!(data_b, 2);
+    }
+
+    #[test]
+    fn skips_operations_that_cannot_be_integrated() {
+        let mut registry = ActionRegistry::new();
+        registry.register::<Set>();
+
+        let (mut a, mut data_a) = (OperationLog::new(Uuid::new_v4()), 0);
+        let (mut b, mut data_b) = (OperationLog::new(Uuid::new_v4()), 0);
+
+        execute(&mut a, &mut data_a, 1);
+        execute(&mut a, &mut data_a, 2);
+
+        let mut unknown = a.entries[0].operation.clone();
+        if let OperationKind::Execute(serialized) = &mut unknown.kind {
+            serialized.tag = "unknown".to_string();
+        }
+
+        b.receive(a.entries[1].operation.clone(), &mut data_b, &registry)
+            .unwrap();
+        assert!(b.receive(unknown, &mut data_b, &registry).is_err());
+
+        // The following operation of the same site is integrated anyway
+        assert_eq!(b.len(), 1);
+        assert_eq!(data_b, 2);
+    }
+}
@@ -0,0 +1,56 @@
+use std::{collections::BTreeMap, fmt};
+
+use rust_editor::actions::SerializedAction;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Identifies a participant of a collaboration session.
+pub type SiteId = Uuid;
+
+/// Uniquely identifies an operation by the site that created it and the number of operations the site created so far.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OperationId {
+    pub site: SiteId,
+    pub sequence: u64,
+}
+
+impl fmt::Display for OperationId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.site, self.sequence)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum OperationKind {
+    /// Executes a new action
+    Execute(SerializedAction),
+
+    /// Undoes the action executed by another operation
+    Undo(OperationId),
+
+    /// Redoes the action executed by another operation
+    Redo(OperationId),
+}
+
+/// A change of the data that is shared between all sites of a collaboration session.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Operation {
+    pub id: OperationId,
+
+    /// Lamport timestamp of the operation. Together with the site it defines the position of the operation in the log,
+    /// which is the same on all sites.
+    pub timestamp: u64,
+
+    /// Number of operations of each site the creating site had integrated when the operation was created. An operation
+    /// is only integrated after all of them.
+    pub dependencies: BTreeMap<SiteId, u64>,
+
+    pub kind: OperationKind,
+}
+
+impl Operation {
+    /// Returns the key that orders operations within the log.
+    pub(crate) fn order(&self) -> (u64, SiteId) {
+        (self.timestamp, self.id.site)
+    }
+}
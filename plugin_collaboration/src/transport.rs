@@ -0,0 +1,114 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{operation::Operation, CollaborationError};
+
+/// Delivers operations between the sites of a collaboration session.
+pub trait Transport {
+    /// Sends `operation` to all other sites.
+    fn send(&mut self, operation: &Operation) -> Result<(), CollaborationError>;
+
+    /// Sets the function that is called for each operation received from another site.
+    fn set_receiver(&mut self, receiver: Box<dyn Fn(Operation)>);
+}
+
+type Receiver = Rc<dyn Fn(Operation)>;
+
+/// Connects transports within the same process. Operations are delivered immediately, which is mostly useful for tests
+/// and for showing several editors on the same page.
+#[derive(Clone, Default)]
+pub struct LoopbackHub {
+    receivers: Rc<RefCell<Vec<Option<Receiver>>>>,
+}
+
+impl LoopbackHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new transport that exchanges operations with all other transports of the hub.
+    pub fn connect(&self) -> LoopbackTransport {
+        let mut receivers = self.receivers.borrow_mut();
+        receivers.push(None);
+
+        LoopbackTransport {
+            hub: self.clone(),
+            index: receivers.len() - 1,
+        }
+    }
+}
+
+pub struct LoopbackTransport {
+    hub: LoopbackHub,
+    index: usize,
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, operation: &Operation) -> Result<(), CollaborationError> {
+        // Collect the receivers first so that they are free to send operations on their own
+        let receivers: Vec<Receiver> = self
+            .hub
+            .receivers
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.index)
+            .filter_map(|(_, receiver)| receiver.clone())
+            .collect();
+
+        for receiver in receivers {
+            receiver(operation.clone());
+        }
+
+        Ok(())
+    }
+
+    fn set_receiver(&mut self, receiver: Box<dyn Fn(Operation)>) {
+        self.hub.receivers.borrow_mut()[self.index] = Some(Rc::from(receiver));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+    use uuid::Uuid;
+
+    use super::{LoopbackHub, Transport};
+    use crate::operation::{Operation, OperationId, OperationKind};
+
+    #[test]
+    fn loopback_delivers_to_other_transports() {
+        let hub = LoopbackHub::new();
+        let mut a = hub.connect();
+        let mut b = hub.connect();
+
+        let received_a = Rc::new(RefCell::new(Vec::new()));
+        let received_b = Rc::new(RefCell::new(Vec::new()));
+
+        let received = Rc::clone(&received_a);
+        a.set_receiver(Box::new(move |operation| {
+            received.borrow_mut().push(operation)
+        }));
+        let received = Rc::clone(&received_b);
+        b.set_receiver(Box::new(move |operation| {
+            received.borrow_mut().push(operation)
+        }));
+
+        let operation = Operation {
+            id: OperationId {
+                site: Uuid::new_v4(),
+                sequence: 1,
+            },
+            timestamp: 1,
+            dependencies: BTreeMap::new(),
+            kind: OperationKind::Undo(OperationId {
+                site: Uuid::new_v4(),
+                sequence: 1,
+            }),
+        };
+        a.send(&operation).unwrap();
+
+        assert!(received_a.borrow().is_empty());
+        assert_eq!(*received_b.borrow(), vec![operation]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_editor::error;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{MessageEvent, WebSocket};
+
+use crate::{operation::Operation, transport::Transport, CollaborationError};
+
+/// Exchanges operations as json text messages over a WebSocket. The server is expected to forward each message to all
+/// other clients of the session.
+pub struct WebSocketTransport {
+    socket: WebSocket,
+
+    /// Messages that were sent before the connection was established
+    queue: Rc<RefCell<Vec<String>>>,
+
+    _on_open: Closure<dyn FnMut(JsValue)>,
+    on_message: Option<Closure<dyn FnMut(MessageEvent)>>,
+}
+
+impl WebSocketTransport {
+    pub fn connect(url: &str) -> Result<Self, CollaborationError> {
+        let socket =
+            WebSocket::new(url).map_err(|e| CollaborationError::Transport(format!("{:?}", e)))?;
+
+        let queue: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let on_open = {
+            let socket = socket.clone();
+            let queue = Rc::clone(&queue);
+
+            Closure::wrap(Box::new(move |_: JsValue| {
+                for message in queue.borrow_mut().drain(..) {
+                    if let Err(e) = socket.send_with_str(&message) {
+                        error!("could not send operation: {:?}", e);
+                    }
+                }
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        Ok(WebSocketTransport {
+            socket,
+            queue,
+            _on_open: on_open,
+            on_message: None,
+        })
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, operation: &Operation) -> Result<(), CollaborationError> {
+        let message = serde_json::to_string(operation)?;
+
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => {
+                self.queue.borrow_mut().push(message);
+                Ok(())
+            }
+            _ => self
+                .socket
+                .send_with_str(&message)
+                .map_err(|e| CollaborationError::Transport(format!("{:?}", e))),
+        }
+    }
+
+    fn set_receiver(&mut self, receiver: Box<dyn Fn(Operation)>) {
+        let on_message = Closure::wrap(Box::new(move |e: MessageEvent| {
+            let text = match e.data().as_string() {
+                Some(text) => text,
+                None => return,
+            };
+
+            match serde_json::from_str::<Operation>(&text) {
+                Ok(operation) => receiver(operation),
+                Err(e) => error!("received invalid operation: {}", e),
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        self.socket
+            .set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        self.on_message = Some(on_message);
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+
+        if let Err(e) = self.socket.close() {
+            error!("could not close connection: {:?}", e);
+        }
+    }
+}
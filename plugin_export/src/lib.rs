@@ -0,0 +1,289 @@
+use std::marker::PhantomData;
+
+use geo::{Coordinate, Rect};
+use plugin_ribbon::model::ribbon_button::RibbonButton;
+use plugin_toolbar::toolbar::ToolbarPosition;
+use rust_editor::{
+    export::{ExportOptions, ExporterInfo},
+    input::keyboard::Key,
+    plugin::{Plugin, PluginWithOptions},
+    ui::{
+        app::{EditorError, Shortkey},
+        dialog::Dialog,
+    },
+};
+use rust_macro::editor_plugin;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlInputElement};
+use yew::Event;
+
+pub enum ExportMessage {
+    /// Shows or hides the export dialog
+    Toggle,
+
+    SetScale(f64),
+    SetFileName(String),
+
+    /// Exports only the given part of the canvas, or all of it if `None`
+    SetRegion(Option<Rect<f64>>),
+}
+
+/// Makes the exporters registered to the editor reachable from the ui.
+///
+/// Every exporter gets a button in the export group of the file tab of the ribbon, which exports with the default
+/// [ExportOptions]. The export dialog, opened with a toolbar button or Ctrl + E, allows to choose the scale, the file
+/// name and the exported region of the canvas before exporting.
+#[editor_plugin(skip)]
+pub struct ExportPlugin<Data> {
+    #[option(skip)]
+    options: ExportOptions,
+
+    #[option(skip)]
+    visible: Rc<RefCell<bool>>,
+
+    #[option(skip)]
+    _data: PhantomData<Data>,
+}
+
+impl<Data> ExportPlugin<Data>
+where
+    Data: Default + 'static,
+{
+    fn message(message: ExportMessage) -> EditorMessages<Data> {
+        EditorMessages::PluginMessage(ExportPlugin::<Data>::identifier(), Box::new(message))
+    }
+
+    fn input_value(e: Event) -> String {
+        let target: EventTarget = e
+            .target()
+            .expect("Event should have a target when dispatched");
+
+        target.unchecked_into::<HtmlInputElement>().value()
+    }
+
+    fn input_checked(e: Event) -> bool {
+        let target: EventTarget = e
+            .target()
+            .expect("Event should have a target when dispatched");
+
+        target.unchecked_into::<HtmlInputElement>().checked()
+    }
+
+    /// Shows inputs for the position and size of the exported region if one is set.
+    fn view_region(&self, canvas_size: Coordinate<i32>, ctx: &Context<App<Data>>) -> Html {
+        let on_toggle = ctx.link().callback(move |e: Event| {
+            let region = Self::input_checked(e).then(|| {
+                Rect::new(
+                    Coordinate { x: 0., y: 0. },
+                    Coordinate {
+                        x: canvas_size.x as f64,
+                        y: canvas_size.y as f64,
+                    },
+                )
+            });
+
+            Self::message(ExportMessage::SetRegion(region))
+        });
+
+        let region = match self.options.region {
+            Some(region) => region,
+            None => {
+                return html! {
+                    <label>
+                        <input type="checkbox" checked=false onchange={on_toggle} />
+                        {"Only a region"}
+                    </label>
+                }
+            }
+        };
+
+        let input =
+            |label: &'static str, value: f64, update: fn(Rect<f64>, f64) -> Option<Rect<f64>>| {
+                let onchange = ctx.link().batch_callback(move |e: Event| {
+                    Self::input_value(e)
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|value| update(region, value))
+                        .map(|region| Self::message(ExportMessage::SetRegion(Some(region))))
+                });
+
+                html! {
+                    <label>
+                        {label}
+                        <input type="number" value={value.to_string()} onchange={onchange} />
+                    </label>
+                }
+            };
+
+        let (min, max) = (region.min(), region.max());
+        html! {
+            <>
+                <label>
+                    <input type="checkbox" checked=true onchange={on_toggle} />
+                    {"Only a region"}
+                </label>
+                {input("X", min.x, |region, x| {
+                    Some(Rect::new(
+                        Coordinate { x, y: region.min().y },
+                        Coordinate { x: x + region.width(), y: region.max().y },
+                    ))
+                })}
+                {input("Y", min.y, |region, y| {
+                    Some(Rect::new(
+                        Coordinate { x: region.min().x, y },
+                        Coordinate { x: region.max().x, y: y + region.height() },
+                    ))
+                })}
+                {input("Width", max.x - min.x, |region, width| {
+                    (width > 0.).then(|| Rect::new(
+                        region.min(),
+                        Coordinate { x: region.min().x + width, y: region.max().y },
+                    ))
+                })}
+                {input("Height", max.y - min.y, |region, height| {
+                    (height > 0.).then(|| Rect::new(
+                        region.min(),
+                        Coordinate { x: region.max().x, y: region.min().y + height },
+                    ))
+                })}
+            </>
+        }
+    }
+
+    fn view_dialog(
+        &self,
+        exporters: Vec<ExporterInfo>,
+        canvas_size: Coordinate<i32>,
+        ctx: &Context<App<Data>>,
+    ) -> Html {
+        let on_scale_change = ctx.link().batch_callback(|e: Event| {
+            Self::input_value(e)
+                .parse::<f64>()
+                .ok()
+                .filter(|scale| *scale > 0.)
+                .map(|scale| Self::message(ExportMessage::SetScale(scale)))
+        });
+        let on_file_name_change = ctx
+            .link()
+            .callback(|e: Event| Self::message(ExportMessage::SetFileName(Self::input_value(e))));
+        let close = ctx
+            .link()
+            .callback(|_| Self::message(ExportMessage::Toggle));
+
+        html! {
+            <Dialog title="Export">
+                <label>
+                    {"File name"}
+                    <input type="text" value={self.options.file_name.clone()} onchange={on_file_name_change} />
+                </label>
+                <label>
+                    {"Scale"}
+                    <input type="number" min="0.1" step="0.1" value={self.options.scale.to_string()} onchange={on_scale_change} />
+                </label>
+                { self.view_region(canvas_size, ctx) }
+
+                <ul class="exporters">
+                {
+                    for exporters.into_iter().map(|info| {
+                        let options = self.options.clone();
+                        let export = ctx
+                            .link()
+                            .callback(move |_| EditorMessages::Export(info.name, options.clone()));
+
+                        html! {
+                            <li>
+                                <button onclick={export}>{format!("{} (.{})", info.label, info.extension)}</button>
+                            </li>
+                        }
+                    })
+                }
+                </ul>
+
+                <button onclick={close}>{"Close"}</button>
+            </Dialog>
+        }
+    }
+}
+
+impl<Data> Plugin<Data> for ExportPlugin<Data>
+where
+    Data: Default + 'static,
+{
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        editor.add_shortkey::<ExportPlugin<Data>>(vec![Key::Ctrl, Key::E])?;
+
+        let visible = Rc::clone(&self.visible);
+        editor.plugin_mut(
+            move |toolbar_plugin: &mut plugin_toolbar::ToolbarPlugin<Data>| {
+                let toolbar = toolbar_plugin
+                    .get_or_add_toolbar("primary.document", ToolbarPosition::Left)
+                    .unwrap();
+
+                let visible = Rc::clone(&visible);
+                toolbar
+                    .add_toggle_button(
+                        "download",
+                        "export",
+                        "Export".to_string(),
+                        move || *visible.as_ref().borrow(),
+                        || Self::message(ExportMessage::Toggle),
+                    )
+                    .unwrap();
+            },
+        );
+
+        // Exporters registered after the plugin was added are only listed in the dialog
+        let exporters = editor.exporters();
+        editor.plugin_mut(|ribbon_plugin: &mut plugin_ribbon::RibbonPlugin<Data>| {
+            let group = ribbon_plugin
+                .get_or_add_tab_mut("file", "File")
+                .unwrap()
+                .get_or_add_group_mut("export", "Export")
+                .unwrap();
+
+            for info in &exporters {
+                let name = info.name;
+                group.add_action(RibbonButton::new(
+                    name,
+                    "download",
+                    Some(format!("Export as {}", info.label)),
+                    None,
+                    move || EditorMessages::Export(name, ExportOptions::default()),
+                ));
+            }
+        });
+
+        Ok(())
+    }
+
+    fn editor_elements(&mut self, ctx: &Context<App<Data>>, editor: &App<Data>) -> Vec<Html> {
+        match *self.visible.as_ref().borrow() {
+            true => vec![self.view_dialog(editor.exporters(), editor.canvas_size(), ctx)],
+            false => Vec::default(),
+        }
+    }
+
+    fn shortkey_pressed(&mut self, key: &Shortkey, _: &Context<App<Data>>, _: &mut App<Data>) {
+        if *key == vec![Key::Ctrl, Key::E] {
+            let mut visible = self.visible.borrow_mut();
+            *visible = !*visible;
+        }
+    }
+
+    fn on_message(&mut self, message: Box<dyn Any>) {
+        let message = match message.downcast::<ExportMessage>() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        match *message {
+            ExportMessage::Toggle => {
+                let mut visible = self.visible.borrow_mut();
+                *visible = !*visible;
+            }
+            ExportMessage::SetScale(scale) => self.options.scale = scale,
+            ExportMessage::SetFileName(file_name) => self.options.file_name = file_name,
+            ExportMessage::SetRegion(region) => self.options.region = region,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use rust_editor::plugin::Plugin;
+use rust_editor::{plugin::Plugin, snapping::SnapGrid, ui::app::EditorError};
 use rust_macro::editor_plugin;
 use web_sys::CanvasRenderingContext2d;
 
@@ -23,11 +23,34 @@ pub struct Grid {
     subdivisions: u8,
 }
 
+impl Grid {
+    /// Lines dragged points snap to, which are the lines drawn by [render_overlay](Plugin::render_overlay).
+    fn snap_grid(&self) -> Option<SnapGrid> {
+        match self.offset {
+            0 => None,
+            offset => Some(SnapGrid {
+                offset: offset as f64,
+                subdivisions: self.subdivisions.max(1) as u32,
+            }),
+        }
+    }
+}
+
 impl<Data> Plugin<Data> for Grid
 where
     Data: Default + 'static,
 {
-    fn render(&self, context: &CanvasRenderingContext2d, _: &App<Data>) {
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        editor.snapping().borrow_mut().set_grid(self.snap_grid());
+
+        Ok(())
+    }
+
+    fn property_updated(&mut self, _property: &str, editor: &mut App<Data>) {
+        editor.snapping().borrow_mut().set_grid(self.snap_grid());
+    }
+
+    fn render_overlay(&self, context: &CanvasRenderingContext2d, _: &App<Data>) {
         if self.offset == 0 {
             return;
         }
@@ -43,7 +66,7 @@ where
         let steps_x = (width as f64 / self.offset as f64).ceil() as u32;
         let steps_y = (height as f64 / self.offset as f64).ceil() as u32;
 
-        let sub_offset = (self.offset as f64 / self.subdivisions as f64).ceil() as u32;
+        let sub_offset = self.offset as f64 / self.subdivisions as f64;
 
         for i in 0..steps_x {
             let i = (i * self.offset).into();
@@ -52,8 +75,8 @@ where
             context.set_line_width(1.0);
             for k in 0..self.subdivisions as u32 {
                 context.begin_path();
-                context.move_to(i + (k * sub_offset) as f64, 0.0);
-                context.line_to(i + (k * sub_offset) as f64, height.into());
+                context.move_to(i + k as f64 * sub_offset, 0.0);
+                context.line_to(i + k as f64 * sub_offset, height.into());
                 context.close_path();
                 context.stroke();
             }
@@ -74,8 +97,8 @@ where
             context.set_line_width(1.0);
             for k in 0..self.subdivisions as u32 {
                 context.begin_path();
-                context.move_to(0., i + (k * sub_offset) as f64);
-                context.line_to(width.into(), i + (k * sub_offset) as f64);
+                context.move_to(0., i + k as f64 * sub_offset);
+                context.line_to(width.into(), i + k as f64 * sub_offset);
                 context.close_path();
                 context.stroke();
             }
@@ -0,0 +1,516 @@
+use std::future::Future;
+
+use plugin_toolbar::toolbar::ToolbarPosition;
+use plugin_ui_components::ComponentsPlugin;
+use plugin_undo_redo::persistence::{HistoryStore, SerializedHistory};
+use rust_editor::{
+    actions::ActionRegistry,
+    input::keyboard::Key,
+    log,
+    plugin::{Plugin, PluginWithOptions},
+    store::{self, DocumentInfo, Library, StoreError, TaskQueue},
+    ui::{
+        app::{EditorError, Shortkey},
+        dialog::Dialog,
+    },
+};
+use rust_macro::editor_plugin;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{EventTarget, HtmlInputElement};
+use yew::Event;
+
+/// Size of the thumbnails saved with each document in pixels
+const THUMBNAIL_WIDTH: u32 = 160;
+const THUMBNAIL_HEIGHT: u32 = 120;
+
+pub enum LibraryMessage<Data> {
+    /// Shows or hides the open document dialog
+    Toggle,
+
+    /// The documents of the library were read
+    Listed {
+        documents: Vec<DocumentInfo>,
+        recent: Vec<DocumentInfo>,
+    },
+
+    /// Replaces the data by an empty, not yet saved document
+    New,
+
+    Open(Uuid),
+
+    /// The data of a document was loaded, together with its undo history if it was saved
+    Opened(Uuid, Data, Option<SerializedHistory>),
+
+    /// Saves the data into the current document or creates a new one if there is none
+    Save,
+
+    /// The data was saved into the document
+    Saved(DocumentInfo),
+
+    Rename(Uuid, String),
+    Duplicate(Uuid),
+    Delete(Uuid),
+
+    /// Opens the file chooser to import files into the current document
+    Import,
+}
+
+struct Storage<Data> {
+    library: Library,
+
+    serialize: fn(&Data) -> Result<Value, StoreError>,
+    deserialize: fn(Value) -> Result<Data, StoreError>,
+}
+
+impl<Data> Storage<Data> {
+    fn history_store(&self, id: Uuid) -> HistoryStore {
+        HistoryStore::with_backend(&self.library.document_name(id), self.library.backend())
+    }
+}
+
+/// Saves the data of the editor as named documents of a [Library].
+///
+/// Adds a dialog to open, rename, duplicate and delete documents, which also lists the recently opened ones. The
+/// dialog is shown with a toolbar button or Ctrl + O, the current document is saved with Ctrl + S. Another toolbar
+/// button imports files with the importers registered to the editor.
+///
+/// If an [ActionRegistry] is set, the undo history is saved with each document and restored when it is opened again.
+/// Only the serializable actions at the top of the stacks are kept, see [SerializedHistory].
+///
+/// # Example
+///
+/// ```
+/// let mut library = DocumentLibrary::<Map>::default();
+/// library.set_library(Library::new("maps", Rc::new(IndexedDbBackend::new("editor", "documents"))));
+/// library.set_action_registry(registry);
+///
+/// editor.add_plugin(library);
+/// ```
+#[editor_plugin]
+pub struct DocumentLibrary<Data> {
+    #[option(
+        default = 5,
+        min = 0,
+        max = 20,
+        label = "Recent documents",
+        description = "Number of recently opened documents that are listed"
+    )]
+    recent: u8,
+
+    #[option(skip)]
+    storage: Option<Rc<Storage<Data>>>,
+
+    /// Restores the saved undo history, `None` if the history is not saved
+    #[option(skip)]
+    registry: Option<ActionRegistry<Data>>,
+
+    #[option(skip)]
+    documents: Vec<DocumentInfo>,
+
+    #[option(skip)]
+    recent_documents: Vec<DocumentInfo>,
+
+    /// The document the data was loaded from, `None` if it was not saved yet
+    #[option(skip)]
+    current: Option<DocumentInfo>,
+
+    /// The document created by the first save of a new document. Shared with the queued saves, so that saving again
+    /// before the first save finished does not create another document.
+    #[option(skip)]
+    created: Rc<RefCell<Option<Uuid>>>,
+
+    #[option(skip)]
+    visible: Rc<RefCell<bool>>,
+
+    #[option(skip)]
+    sender: Option<Callback<EditorMessages<Data>>>,
+
+    #[option(skip)]
+    queue: TaskQueue,
+}
+
+impl<Data> DocumentLibrary<Data>
+where
+    Data: Default + 'static,
+{
+    /// Saves the documents in `library`. Needs to be called before the plugin is added to the editor, the plugin is
+    /// disabled otherwise.
+    pub fn set_library(&mut self, library: Library)
+    where
+        Data: Serialize + DeserializeOwned,
+    {
+        self.storage = Some(Rc::new(Storage {
+            library,
+            serialize: |data| {
+                serde_json::to_value(data).map_err(|e| StoreError::Serialization(Box::new(e)))
+            },
+            deserialize: store::from_value,
+        }));
+    }
+
+    /// Saves the undo history with each document and restores it with the actions registered to `registry`.
+    pub fn set_action_registry(&mut self, registry: ActionRegistry<Data>) {
+        self.registry = Some(registry);
+    }
+
+    /// Returns the document the data was loaded from or last saved to.
+    pub fn current(&self) -> Option<&DocumentInfo> {
+        self.current.as_ref()
+    }
+
+    fn message(message: LibraryMessage<Data>) -> EditorMessages<Data> {
+        EditorMessages::PluginMessage(DocumentLibrary::<Data>::identifier(), Box::new(message))
+    }
+
+    /// Runs `task` with the storage after all previously spawned tasks and reports it to the editor if it fails, see
+    /// [EditorMessages::StoreFailed].
+    fn spawn<F>(&self, task: impl FnOnce(Rc<Storage<Data>>, Callback<EditorMessages<Data>>) -> F)
+    where
+        F: Future<Output = Result<(), StoreError>> + 'static,
+    {
+        let (storage, sender) = match (&self.storage, &self.sender) {
+            (Some(storage), Some(sender)) => (Rc::clone(storage), sender.clone()),
+            _ => return,
+        };
+
+        let task = task(storage, sender.clone());
+        self.queue.spawn(async move {
+            if let Err(e) = task.await {
+                sender.emit(EditorMessages::StoreFailed(e));
+            }
+        });
+    }
+
+    fn refresh(&self) {
+        let recent = self.recent as usize;
+
+        self.spawn(|storage, sender| async move {
+            let documents = storage.library.list().await?;
+            let recent = storage.library.recent(recent).await?;
+
+            sender.emit(Self::message(LibraryMessage::Listed { documents, recent }));
+
+            Ok(())
+        });
+    }
+
+    fn save(&self, editor: &App<Data>) {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        let value = match (storage.serialize)(editor.data()) {
+            Ok(value) => value,
+            Err(e) => {
+                if let Some(sender) = &self.sender {
+                    sender.emit(EditorMessages::StoreFailed(e));
+                }
+                return;
+            }
+        };
+
+        let thumbnail = editor.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+        let current = self.current.as_ref().map(|info| info.id);
+        let created = Rc::clone(&self.created);
+        let history = self
+            .registry
+            .as_ref()
+            .map(|_| SerializedHistory::from_editor(editor));
+
+        self.spawn(move |storage, sender| async move {
+            let id = current.or(*(*created).borrow());
+            let info = match id {
+                Some(id) => storage.library.save(id, &value, thumbnail).await?,
+                None => {
+                    let info = storage
+                        .library
+                        .create("Untitled", &value, thumbnail)
+                        .await?;
+                    *(*created).borrow_mut() = Some(info.id);
+                    info
+                }
+            };
+
+            if let Some(history) = history {
+                storage.history_store(info.id).save(&history).await?;
+            }
+
+            sender.emit(Self::message(LibraryMessage::Saved(info)));
+
+            Ok(())
+        });
+    }
+
+    fn open(&self, id: Uuid) {
+        let with_history = self.registry.is_some();
+
+        self.spawn(move |storage, sender| async move {
+            let value = storage.library.open::<Value>(id).await?;
+            let data = (storage.deserialize)(value)?;
+
+            // A history that cannot be read must not keep the document from being opened
+            let history = match with_history {
+                true => storage.history_store(id).load().await.unwrap_or_else(|e| {
+                    sender.emit(EditorMessages::StoreFailed(e));
+                    None
+                }),
+                false => None,
+            };
+
+            sender.emit(Self::message(LibraryMessage::Opened(id, data, history)));
+
+            Ok(())
+        });
+    }
+
+    fn rename(&self, id: Uuid, title: String) {
+        self.spawn(move |storage, _| async move {
+            storage.library.rename(id, &title).await?;
+            Ok(())
+        });
+        self.refresh();
+    }
+
+    fn duplicate(&self, id: Uuid) {
+        let title = match self.documents.iter().find(|info| info.id == id) {
+            Some(info) => format!("{} (copy)", info.title),
+            None => return,
+        };
+
+        self.spawn(move |storage, _| async move {
+            storage.library.duplicate(id, &title).await?;
+            Ok(())
+        });
+        self.refresh();
+    }
+
+    fn delete(&self, id: Uuid) {
+        self.spawn(move |storage, _| async move {
+            storage.library.delete(id).await?;
+            storage.history_store(id).remove().await
+        });
+        self.refresh();
+    }
+
+    fn view_document(&self, info: &DocumentInfo, ctx: &Context<App<Data>>) -> Html {
+        let id = info.id;
+
+        let onchange = ctx.link().callback(move |e: Event| {
+            let target: EventTarget = e
+                .target()
+                .expect("Event should have a target when dispatched");
+
+            Self::message(LibraryMessage::Rename(
+                id,
+                target.unchecked_into::<HtmlInputElement>().value(),
+            ))
+        });
+        let open = ctx
+            .link()
+            .callback(move |_| Self::message(LibraryMessage::Open(id)));
+        let duplicate = ctx
+            .link()
+            .callback(move |_| Self::message(LibraryMessage::Duplicate(id)));
+        let delete = ctx
+            .link()
+            .callback(move |_| Self::message(LibraryMessage::Delete(id)));
+
+        let modified = js_sys::Date::new(&JsValue::from_f64(info.modified))
+            .to_locale_string("default", &JsValue::UNDEFINED);
+
+        html! {
+            <li>
+                if let Some(thumbnail) = &info.thumbnail {
+                    <img class="thumbnail" src={thumbnail.clone()} />
+                }
+                <input type="text" value={info.title.clone()} {onchange} />
+                <span class="modified">{String::from(modified)}</span>
+                <button onclick={open}>{"Open"}</button>
+                <button onclick={duplicate}>{"Duplicate"}</button>
+                <button onclick={delete}>{"Delete"}</button>
+            </li>
+        }
+    }
+
+    fn view_library(&self, ctx: &Context<App<Data>>) -> Html {
+        let new = ctx.link().callback(|_| Self::message(LibraryMessage::New));
+        let close = ctx
+            .link()
+            .callback(|_| Self::message(LibraryMessage::Toggle));
+
+        html! {
+            <Dialog title="Open document">
+                if !self.recent_documents.is_empty() {
+                    <h6>{"Recent"}</h6>
+                    <ul class="library_recent">
+                    {
+                        for self.recent_documents.iter().map(|info| {
+                            let id = info.id;
+                            let open = ctx
+                                .link()
+                                .callback(move |_| Self::message(LibraryMessage::Open(id)));
+
+                            html! {
+                                <li><button onclick={open}>{info.title.clone()}</button></li>
+                            }
+                        })
+                    }
+                    </ul>
+                }
+
+                <h6>{"All documents"}</h6>
+                <ul class="library_documents">
+                    { for self.documents.iter().map(|info| self.view_document(info, ctx)) }
+                </ul>
+
+                <button onclick={new}>{"New document"}</button>
+                <button onclick={close}>{"Close"}</button>
+            </Dialog>
+        }
+    }
+}
+
+impl<Data> Plugin<Data> for DocumentLibrary<Data>
+where
+    Data: Default + 'static,
+{
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        if self.storage.is_none() {
+            log!("document library is disabled, call DocumentLibrary::set_library before adding the plugin");
+            return Ok(());
+        }
+
+        self.sender = editor.message_callback();
+
+        editor.add_shortkey::<DocumentLibrary<Data>>(vec![Key::Ctrl, Key::S])?;
+        editor.add_shortkey::<DocumentLibrary<Data>>(vec![Key::Ctrl, Key::O])?;
+
+        let visible = Rc::clone(&self.visible);
+        editor.plugin_mut(
+            move |toolbar_plugin: &mut plugin_toolbar::ToolbarPlugin<Data>| {
+                let toolbar = toolbar_plugin
+                    .get_or_add_toolbar("primary.document", ToolbarPosition::Left)
+                    .unwrap();
+
+                let visible = Rc::clone(&visible);
+                toolbar
+                    .add_toggle_button(
+                        "folder_open",
+                        "open_document",
+                        "Open document".to_string(),
+                        move || *visible.as_ref().borrow(),
+                        || Self::message(LibraryMessage::Toggle),
+                    )
+                    .unwrap();
+
+                toolbar
+                    .add_button("save", "save_document", "Save".to_string(), || {
+                        Self::message(LibraryMessage::Save)
+                    })
+                    .unwrap();
+
+                toolbar
+                    .add_button("upload_file", "import_file", "Import".to_string(), || {
+                        Self::message(LibraryMessage::Import)
+                    })
+                    .unwrap();
+            },
+        );
+
+        self.refresh();
+
+        Ok(())
+    }
+
+    fn editor_elements(&mut self, ctx: &Context<App<Data>>, _: &App<Data>) -> Vec<Html> {
+        match *self.visible.as_ref().borrow() {
+            true => vec![self.view_library(ctx)],
+            false => Vec::default(),
+        }
+    }
+
+    fn shortkey_pressed(&mut self, key: &Shortkey, _: &Context<App<Data>>, editor: &mut App<Data>) {
+        if *key == vec![Key::Ctrl, Key::S] {
+            self.save(editor);
+        } else if *key == vec![Key::Ctrl, Key::O] {
+            let mut visible = self.visible.borrow_mut();
+            *visible = !*visible;
+        }
+    }
+
+    fn on_message_with_editor(&mut self, message: Box<dyn Any>, editor: &mut App<Data>) {
+        let message = match message.downcast::<LibraryMessage<Data>>() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        match *message {
+            LibraryMessage::Toggle => {
+                let mut visible = self.visible.borrow_mut();
+                *visible = !*visible;
+            }
+            LibraryMessage::Listed { documents, recent } => {
+                // Keeps the title of the current document in sync with renames
+                if let Some(current) = self.current.as_mut() {
+                    if let Some(info) = documents.iter().find(|info| info.id == current.id) {
+                        *current = info.clone();
+                    }
+                }
+
+                self.documents = documents;
+                self.recent_documents = recent;
+            }
+            LibraryMessage::New => {
+                editor.set_data(Data::default());
+                editor.history_mut().clear();
+
+                self.current = None;
+                self.created = Rc::default();
+                *self.visible.borrow_mut() = false;
+            }
+            LibraryMessage::Open(id) => self.open(id),
+            LibraryMessage::Opened(id, data, history) => {
+                editor.set_data(data);
+                editor.history_mut().clear();
+
+                if let (Some(history), Some(registry)) = (history, &self.registry) {
+                    if let Err(e) = history.restore(editor, registry) {
+                        if let Some(sender) = &self.sender {
+                            ComponentsPlugin::notify_error(sender, &e);
+                        }
+                    }
+                }
+
+                self.current = self.documents.iter().find(|info| info.id == id).cloned();
+                self.created = Rc::default();
+                *self.visible.borrow_mut() = false;
+
+                self.refresh();
+            }
+            LibraryMessage::Save => self.save(editor),
+            LibraryMessage::Saved(info) => {
+                if let Some(sender) = &self.sender {
+                    ComponentsPlugin::notify(sender, format!("Saved {}", info.title));
+                }
+
+                self.current = Some(info);
+                self.refresh();
+            }
+            LibraryMessage::Rename(id, title) => self.rename(id, title),
+            LibraryMessage::Duplicate(id) => self.duplicate(id),
+            LibraryMessage::Delete(id) => {
+                if self.current.as_ref().map(|info| info.id) == Some(id) {
+                    self.current = None;
+                    self.created = Rc::default();
+                }
+
+                self.delete(id);
+            }
+            LibraryMessage::Import => editor.choose_import_files(),
+        }
+    }
+}
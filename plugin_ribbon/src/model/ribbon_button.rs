@@ -25,7 +25,7 @@ pub enum RibbonButtonState {
 pub struct RibbonButton<Data> {
     pub icon: &'static str,
     pub identifier: &'static str,
-    pub tooltip: Option<String>,
+    pub tooltip: Rc<RefCell<Option<String>>>,
     pub state: Rc<RefCell<RibbonButtonState>>,
     pub button_type: RibbonButtonType,
     pub on_click_callback: Rc<dyn Fn() -> EditorMessages<Data>>,
@@ -45,7 +45,7 @@ where
         RibbonButton {
             identifier,
             icon,
-            tooltip,
+            tooltip: Rc::new(RefCell::new(tooltip)),
             state: Rc::new(RefCell::new(RibbonButtonState::Enabled)),
             button_type: button_type.unwrap_or_default(),
             on_click_callback: Rc::new(on_click_callback),
@@ -55,6 +55,10 @@ where
     pub fn set_state(&mut self, new_state: RibbonButtonState) {
         *self.state.borrow_mut() = new_state;
     }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<String>) {
+        *self.tooltip.borrow_mut() = tooltip;
+    }
 }
 
 impl<Data> RibbonAction<Data> for RibbonButton<Data>
@@ -91,14 +95,17 @@ where
             RibbonButtonState::Selected => "selected",
         };
 
+        let disabled = *self.state.as_ref().borrow() == RibbonButtonState::Disabled;
+        let title = self.tooltip.as_ref().borrow().clone();
+
         html! {
-            <button class={state_class} onclick={onclick} /*onmouseover={onmouseover} onmouseleave={onmouseleave}*/ >
+            <button class={state_class} onclick={onclick} {disabled} {title} /*onmouseover={onmouseover} onmouseleave={onmouseleave}*/ >
                 <span class="material-icons">{&self.icon}</span>
                 {
                     html! {
                         /*
                         if show_tooltip {
-                            if let Some(tooltip) = &self.tooltip {
+                            if let Some(tooltip) = &*self.tooltip.as_ref().borrow() {
                                 <Tooltip text={tooltip.clone()} position={TooltipPosition::Below} />
                             }
                         }
@@ -12,9 +12,10 @@ pub mod view;
 pub struct ToolbarButton<Data> {
     pub icon: &'static str,
     pub identifier: &'static str,
-    pub tooltip: String,
+    pub tooltip: Box<dyn Fn() -> String>,
     pub on_click_callback: Rc<dyn Fn() -> EditorMessages<Data>>,
     pub selected: Option<Box<dyn Fn() -> bool>>,
+    pub enabled: Option<Box<dyn Fn() -> bool>>,
   }
 
 #[editor_plugin(skip)]
@@ -105,16 +106,21 @@ where
             }
         }
 
+        let disabled = match &button.enabled {
+            Some(enabled_callback) => !enabled_callback(),
+            None => false,
+        };
+
         let callback = Rc::clone(&button.on_click_callback);
         let onclick = ctx.link().callback(move |_| (*callback)());
         html! {
             <li>
-            <button onclick={onclick} class={classes}>
+            <button onclick={onclick} class={classes} {disabled} title={(button.tooltip)()}>
               <span class="material-icons">{button.icon}</span>
 
             </button>
             /*
-            <span class="tooltip">{&button.tooltip}</span>
+            <span class="tooltip">{(button.tooltip)()}</span>
             */
           </li>
         }
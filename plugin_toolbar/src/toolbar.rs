@@ -41,9 +41,10 @@ impl<Data> Toolbar<Data> {
         let btn = ToolbarButton {
             icon,
             identifier,
-            tooltip,
+            tooltip: Box::new(move || tooltip.clone()),
             on_click_callback: Rc::new(on_click_callback),
             selected: None,
+            enabled: None,
         };
 
         self.buttons.push(btn);
@@ -62,9 +63,34 @@ impl<Data> Toolbar<Data> {
         let btn = ToolbarButton {
             icon,
             identifier,
-            tooltip,
+            tooltip: Box::new(move || tooltip.clone()),
             on_click_callback: Rc::new(on_click_callback),
             selected: Some(Box::new(toggled)),
+            enabled: None,
+        };
+
+        self.buttons.push(btn);
+
+        Ok(())
+    }
+
+    /// Adds a button that is disabled as long as `enabled` returns false. Both `enabled` and `tooltip` are evaluated
+    /// each time the toolbar is rendered, so the tooltip can describe what a click would do right now.
+    pub fn add_conditional_button(
+        &mut self,
+        icon: &'static str,
+        identifier: &'static str,
+        tooltip: impl Fn() -> String + 'static,
+        enabled: impl Fn() -> bool + 'static,
+        on_click_callback: impl Fn() -> EditorMessages<Data> + 'static,
+    ) -> Result<(), EditorError> {
+        let btn = ToolbarButton {
+            icon,
+            identifier,
+            tooltip: Box::new(tooltip),
+            on_click_callback: Rc::new(on_click_callback),
+            selected: None,
+            enabled: Some(Box::new(enabled)),
         };
 
         self.buttons.push(btn);
@@ -100,16 +126,21 @@ where
             }
         }
 
+        let disabled = match &button.enabled {
+            Some(enabled_callback) => !enabled_callback(),
+            None => false,
+        };
+
         let callback = Rc::clone(&button.on_click_callback);
         let onclick = ctx.link().callback(move |_| (*callback)());
         html! {
             <li>
-            <button onclick={onclick} class={classes}>
+            <button onclick={onclick} class={classes} {disabled} title={(button.tooltip)()}>
               <span class="material-icons">{button.icon}</span>
 
             </button>
             /*
-            <span class="tooltip">{&button.tooltip}</span>
+            <span class="tooltip">{(button.tooltip)()}</span>
             */
           </li>
         }
@@ -1,6 +1,7 @@
 use crate::snackbar::Snackbar;
-use rust_editor::log;
-use rust_editor::plugin::Plugin;
+#[cfg(feature = "snackbar")]
+use rust_editor::{export::ExportEvent, import::ImportEvent};
+use rust_editor::plugin::{Plugin, PluginWithOptions};
 use rust_editor::ui::app::{EditorError, Shortkey};
 use rust_macro::editor_plugin;
 
@@ -18,11 +19,7 @@ pub struct ComponentsPlugin {
 
 pub enum ComponentMessage {
     #[cfg(feature = "snackbar")]
-    ShowSnackbar(
-        &'static str,
-        Option<SnackbarPosition>,
-        Option<SnackbarAction>,
-    ),
+    ShowSnackbar(String, Option<SnackbarPosition>, Option<SnackbarAction>),
 }
 
 unsafe impl Send for ComponentsPlugin {}
@@ -31,15 +28,10 @@ impl ComponentsPlugin {
     #[cfg(feature = "snackbar")]
     pub fn show_snackbar(
         &mut self,
-        text: &'static str,
+        text: String,
         position: Option<SnackbarPosition>,
-        _: Option<SnackbarAction>,
+        action: Option<SnackbarAction>,
     ) {
-        let action = SnackbarAction {
-            label: "Retry".to_string(),
-            callback: Rc::new(|| log!("Click on retry")),
-        };
-
         let position = position.unwrap_or_default();
         let snackbar = html! {
             <Snackbar message={text} action={action} position={position} />
@@ -47,13 +39,61 @@ impl ComponentsPlugin {
 
         self.elements.push(snackbar);
     }
+
+    /// Shows `text` in a snackbar. Unlike [ComponentsPlugin::show_snackbar] this only needs the message callback of the
+    /// editor, so it can be used after asynchronous operations like saving to a [rust_editor::store::Store].
+    #[cfg(feature = "snackbar")]
+    pub fn notify<Data>(sender: &Callback<EditorMessages<Data>>, text: impl Into<String>)
+    where
+        Data: Default + 'static,
+    {
+        sender.emit(EditorMessages::PluginMessage(
+            <ComponentsPlugin as PluginWithOptions<Data>>::identifier(),
+            Box::new(ComponentMessage::ShowSnackbar(text.into(), None, None)),
+        ));
+    }
+
+    /// Shows `error` in a snackbar, see [ComponentsPlugin::notify].
+    #[cfg(feature = "snackbar")]
+    pub fn notify_error<Data>(
+        sender: &Callback<EditorMessages<Data>>,
+        error: &dyn std::error::Error,
+    ) where
+        Data: Default + 'static,
+    {
+        ComponentsPlugin::notify(sender, error.to_string())
+    }
 }
 
 impl<Data> Plugin<Data> for ComponentsPlugin
 where
     Data: Default + 'static,
 {
-    fn startup(&mut self, _: &mut App<Data>) -> Result<(), EditorError> {
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        // Files are imported asynchronously, so failures can only be reported once reading them finished. Export and store
+        // failures are reported the same way, since exports are started with editor messages and stores run
+        // asynchronously.
+        #[cfg(feature = "snackbar")]
+        if let Some(sender) = editor.message_callback() {
+            let import_sender = sender.clone();
+            editor.listen_imports(move |event| {
+                if let ImportEvent::Failed(error) = event {
+                    ComponentsPlugin::notify_error(&import_sender, *error);
+                }
+            });
+
+            let export_sender = sender.clone();
+            editor.listen_exports(move |event| {
+                if let ExportEvent::Failed(error) = event {
+                    ComponentsPlugin::notify_error(&export_sender, *error);
+                }
+            });
+
+            editor.listen_store_failures(move |error| {
+                ComponentsPlugin::notify_error(&sender, error);
+            });
+        }
+
         Ok(())
     }
 
@@ -64,13 +104,15 @@ where
     }
 
     fn on_message(&mut self, message: Box<dyn Any>) {
-        let message = message.as_ref().downcast_ref::<ComponentMessage>().unwrap();
+        let message = match message.downcast::<ComponentMessage>() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
 
-        // TODO enable option
-        match message {
+        match *message {
             #[cfg(feature = "snackbar")]
-            ComponentMessage::ShowSnackbar(text, position, _) => {
-                self.show_snackbar(text, position.clone(), None)
+            ComponentMessage::ShowSnackbar(text, position, action) => {
+                self.show_snackbar(text, position, action)
             }
         }
     }
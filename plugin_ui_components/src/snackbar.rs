@@ -112,7 +112,7 @@ pub fn Snackbar(props: &SnackbarProps) -> Html {
                         };
 
                         html! {
-                            <button onclick={onclick}>{"ACTION"}</button>
+                            <button onclick={onclick}>{action.label.clone()}</button>
                         }
                     } else {
                         html! {
@@ -0,0 +1,270 @@
+use std::marker::PhantomData;
+
+use rust_macro::editor_plugin;
+
+use rust_editor::{
+    history::{HistoryNode, HistoryNodeState},
+    plugin::{Plugin, PluginWithOptions},
+    ui::app::EditorError,
+};
+
+use plugin_toolbar::toolbar::ToolbarPosition;
+
+pub enum HistoryPanelMessage {
+    /// Undoes or redoes actions until the given number of actions is applied to the data.
+    JumpTo(usize),
+
+    /// Jumps to the node of the undo tree at the given path, see [History::jump_to_node](rust_editor::history::History::jump_to_node).
+    JumpToNode(Vec<usize>),
+
+    /// Replaces the most recent action with its next (`true`) or previous sibling branch.
+    SwitchBranch(bool),
+
+    /// Shows or hides the panel.
+    Toggle,
+}
+
+/// Lists all actions that can be undone or redone. Clicking on an entry restores the state of the data right after the
+/// action was executed.
+///
+/// With branching enabled, actions that are undone and then replaced by another action are kept and shown as branches of
+/// an undo tree.
+#[editor_plugin]
+pub struct HistoryPanel<Data> {
+    #[option(
+        default = false,
+        label = "Branching history",
+        description = "Keeps undone actions as branches instead of discarding them"
+    )]
+    branching: bool,
+
+    #[option(skip)]
+    visible: Rc<RefCell<bool>>,
+
+    #[option(skip)]
+    _data: PhantomData<Data>,
+}
+
+impl<Data> HistoryPanel<Data>
+where
+    Data: Default + 'static,
+{
+    fn view_entry(
+        &self,
+        text: String,
+        index: usize,
+        position: usize,
+        ctx: &Context<App<Data>>,
+    ) -> Html {
+        let class = match index {
+            i if i == position => "current",
+            i if i < position => "past",
+            _ => "future",
+        };
+
+        let onclick = ctx.link().callback(move |_| {
+            EditorMessages::PluginMessage(
+                HistoryPanel::<Data>::identifier(),
+                Box::new(HistoryPanelMessage::JumpTo(index)),
+            )
+        });
+
+        html! {
+            <li class={class} {onclick}>{text}</li>
+        }
+    }
+
+    fn view_node(&self, node: &HistoryNode, path: Vec<usize>, ctx: &Context<App<Data>>) -> Html {
+        let class = match node.state {
+            HistoryNodeState::Past => "past",
+            HistoryNodeState::Current => "current",
+            HistoryNodeState::Future => "future",
+            HistoryNodeState::Branch => "branch",
+        };
+
+        let onclick = ctx.link().callback(move |_| {
+            EditorMessages::PluginMessage(
+                HistoryPanel::<Data>::identifier(),
+                Box::new(HistoryPanelMessage::JumpToNode(path.clone())),
+            )
+        });
+
+        html! {
+            <li class={class} {onclick}>{&node.label}</li>
+        }
+    }
+
+    /// Renders `node` and its first children one below the other. All other children fork off and are rendered as
+    /// indented lists in between.
+    fn view_tree(&self, node: &HistoryNode, path: Vec<usize>, ctx: &Context<App<Data>>) -> Html {
+        let mut entries: Vec<Html> = vec![];
+        let mut node = node;
+        let mut path = path;
+
+        loop {
+            entries.push(self.view_node(node, path.clone(), ctx));
+
+            for (index, branch) in node.children.iter().enumerate().skip(1) {
+                let mut branch_path = path.clone();
+                branch_path.push(index);
+
+                entries.push(html! {
+                    <li class="fork">{ self.view_tree(branch, branch_path, ctx) }</li>
+                });
+            }
+
+            match node.children.first() {
+                Some(next) => {
+                    node = next;
+                    path.push(0);
+                }
+                None => break,
+            }
+        }
+
+        html! {
+            <ul>{ for entries }</ul>
+        }
+    }
+
+    fn view_branching(&self, ctx: &Context<App<Data>>, editor: &App<Data>) -> Html {
+        let history = editor.history();
+        let root = HistoryNode {
+            label: "Initial state".to_string(),
+            state: match history.position() {
+                0 => HistoryNodeState::Current,
+                _ => HistoryNodeState::Past,
+            },
+            children: history.tree(),
+        };
+
+        let switch_branch = |forward: bool| {
+            ctx.link().callback(move |_| {
+                EditorMessages::PluginMessage(
+                    HistoryPanel::<Data>::identifier(),
+                    Box::new(HistoryPanelMessage::SwitchBranch(forward)),
+                )
+            })
+        };
+
+        html! {
+            <div class="history_panel">
+                <h2>{"History"}</h2>
+                <div class="branch_navigation">
+                    <button onclick={switch_branch(false)} title="Previous branch">
+                        <span class="material-icons">{"chevron_left"}</span>
+                    </button>
+                    <button onclick={switch_branch(true)} title="Next branch">
+                        <span class="material-icons">{"chevron_right"}</span>
+                    </button>
+                </div>
+                { self.view_tree(&root, vec![], ctx) }
+            </div>
+        }
+    }
+}
+
+impl<Data> Plugin<Data> for HistoryPanel<Data>
+where
+    Data: Default + 'static,
+{
+    fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
+        editor.history_mut().set_branching(self.branching);
+
+        let visible = Rc::clone(&self.visible);
+
+        editor.plugin_mut(
+            move |toolbar_plugin: &mut plugin_toolbar::ToolbarPlugin<Data>| {
+                let toolbar = toolbar_plugin
+                    .get_or_add_toolbar("primary.undo_redo", ToolbarPosition::Left)
+                    .unwrap();
+
+                let visible = Rc::clone(&visible);
+                toolbar
+                    .add_toggle_button(
+                        "history",
+                        "history",
+                        "History".to_string(),
+                        move || *visible.as_ref().borrow(),
+                        || {
+                            EditorMessages::PluginMessage(
+                                HistoryPanel::<Data>::identifier(),
+                                Box::new(HistoryPanelMessage::Toggle),
+                            )
+                        },
+                    )
+                    .unwrap();
+            },
+        );
+
+        Ok(())
+    }
+
+    fn editor_elements(&mut self, ctx: &Context<App<Data>>, editor: &App<Data>) -> Vec<Html> {
+        if !*self.visible.as_ref().borrow() {
+            return Vec::default();
+        }
+
+        if self.branching {
+            return vec![self.view_branching(ctx, editor)];
+        }
+
+        // Entries are ordered from the oldest to the newest state. The first one is the state before any action was
+        // executed, so the index of an entry equals the number of actions that need to be applied to reach it.
+        let history = editor.history();
+        let mut entries: Vec<String> = vec!["Initial state".to_string()];
+        entries.extend(
+            history
+                .undo_stack()
+                .iter()
+                .map(|action| action.as_ref().borrow().to_string()),
+        );
+
+        let position = history.position();
+        entries.extend(
+            history
+                .redo_stack()
+                .iter()
+                .rev()
+                .map(|action| action.as_ref().borrow().to_string()),
+        );
+
+        let element = html! {
+            <div class="history_panel">
+                <h2>{"History"}</h2>
+                <ul>
+                {
+                    for entries.into_iter().enumerate().map(|(index, text)| {
+                        self.view_entry(text, index, position, ctx)
+                    })
+                }
+                </ul>
+            </div>
+        };
+
+        vec![element]
+    }
+
+    fn property_updated(&mut self, property: &str, editor: &mut App<Data>) {
+        if property == "branching" {
+            editor.history_mut().set_branching(self.branching);
+        }
+    }
+
+    fn on_message_with_editor(&mut self, message: Box<dyn Any>, editor: &mut App<Data>) {
+        match message.as_ref().downcast_ref::<HistoryPanelMessage>() {
+            Some(HistoryPanelMessage::JumpTo(position)) => editor.jump_in_history(*position),
+            Some(HistoryPanelMessage::JumpToNode(path)) => {
+                editor.jump_to_history_node(path);
+            }
+            Some(HistoryPanelMessage::SwitchBranch(forward)) => {
+                editor.switch_history_branch(*forward);
+            }
+            Some(HistoryPanelMessage::Toggle) => {
+                let visible = !*self.visible.as_ref().borrow();
+                *self.visible.borrow_mut() = visible;
+            }
+            None => {}
+        }
+    }
+}
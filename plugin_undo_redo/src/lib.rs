@@ -1,5 +1,8 @@
+pub mod history_panel;
+pub mod persistence;
 pub mod redo;
 pub mod undo;
 
+pub use self::history_panel::HistoryPanel;
 pub use self::redo::Redo;
-pub use self::undo::Undo;
\ No newline at end of file
+pub use self::undo::Undo;
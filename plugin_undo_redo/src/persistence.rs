@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_editor::{
+    actions::{Action, ActionRegistry, SerializedAction},
+    store::{StorageBackend, Store, StoreError},
+    ui::app::App,
+};
+use serde::{Deserialize, Serialize};
+
+type Stack<Data> = Vec<Rc<RefCell<dyn Action<Data>>>>;
+
+/// Serializable form of the undo and redo stacks. Both stacks keep their order, so the last action is always the next one
+/// to undo respectively redo.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct SerializedHistory {
+    pub undo: Vec<SerializedAction>,
+    pub redo: Vec<SerializedAction>,
+}
+
+/// Serializes the actions of a stack starting from the top. Serialization stops at the first action that is not
+/// serializable since all actions below it depend on the changes it made.
+fn serialize_stack<Data>(stack: &[Rc<RefCell<dyn Action<Data>>>]) -> Vec<SerializedAction> {
+    let mut actions: Vec<SerializedAction> = stack
+        .iter()
+        .rev()
+        .map_while(|action| action.borrow().to_serialized())
+        .collect();
+    actions.reverse();
+
+    actions
+}
+
+fn deserialize_stack<Data>(
+    actions: Vec<SerializedAction>,
+    registry: &ActionRegistry<Data>,
+) -> Result<Stack<Data>, serde_json::Error>
+where
+    Data: 'static,
+{
+    let mut stack: Stack<Data> = Vec::with_capacity(actions.len());
+    for action in actions {
+        stack.push(Rc::new(RefCell::new(registry.deserialize(action)?)));
+    }
+
+    Ok(stack)
+}
+
+impl SerializedHistory {
+    /// Serializes the current undo and redo stacks of the editor.
+    pub fn from_editor<Data>(editor: &App<Data>) -> Self
+    where
+        Data: Default + 'static,
+    {
+        SerializedHistory {
+            undo: serialize_stack(editor.history().undo_stack()),
+            redo: serialize_stack(editor.history().redo_stack()),
+        }
+    }
+
+    /// Replaces the undo and redo stacks of the editor with the deserialized actions. The actions are not executed,
+    /// the data of the editor is expected to already reflect them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if one of the actions cannot be deserialized. The stacks of the editor are left untouched in that case.
+    pub fn restore<Data>(
+        self,
+        editor: &mut App<Data>,
+        registry: &ActionRegistry<Data>,
+    ) -> Result<(), serde_json::Error>
+    where
+        Data: Default + 'static,
+    {
+        let undo_stack = deserialize_stack(self.undo, registry)?;
+        let redo_stack = deserialize_stack(self.redo, registry)?;
+
+        editor.history_mut().replace(undo_stack, redo_stack);
+
+        Ok(())
+    }
+}
+
+/// Persists the undo and redo history next to a document that is saved with [Store].
+///
+/// # Example
+///
+/// ```
+/// let document = Store::new("map")?;
+/// let history = HistoryStore::new("map")?;
+///
+/// // Saving
+/// document.sync(editor.data()).await?;
+/// history.save(&SerializedHistory::from_editor(editor)).await?;
+///
+/// // Loading
+/// editor.set_data(document.fetch().await?.unwrap_or_default());
+/// if let Some(history) = history.load().await? {
+///     history.restore(editor, &registry)?;
+/// }
+/// ```
+pub struct HistoryStore {
+    store: Store,
+}
+
+impl HistoryStore {
+    /// Creates a store for the history of the document saved under `document_name` in the local storage.
+    pub fn new(document_name: &str) -> Result<Self, StoreError> {
+        Store::new(&Self::key(document_name)).map(|store| HistoryStore { store })
+    }
+
+    /// Creates a store for the history of the document saved under `document_name` in `backend`.
+    pub fn with_backend(document_name: &str, backend: Rc<dyn StorageBackend>) -> Self {
+        HistoryStore {
+            store: Store::with_backend(&Self::key(document_name), backend),
+        }
+    }
+
+    fn key(document_name: &str) -> String {
+        format!("{}.history", document_name)
+    }
+
+    pub async fn save(&self, history: &SerializedHistory) -> Result<(), StoreError> {
+        self.store.sync(history).await
+    }
+
+    /// Loads a previously saved history. Returns `None` if no history was saved so far.
+    pub async fn load(&self) -> Result<Option<SerializedHistory>, StoreError> {
+        self.store.fetch::<SerializedHistory>().await
+    }
+
+    /// Deletes the saved history, e.g. once its document was deleted.
+    pub async fn remove(&self) -> Result<(), StoreError> {
+        self.store.remove().await
+    }
+}
@@ -1,37 +1,29 @@
+use std::marker::PhantomData;
+
 use rust_macro::editor_plugin;
 
 use rust_editor::{
-    actions::Action,
+    history::HistoryState,
     input::keyboard::Key,
-    ui::{
-        app::{EditorError, Shortkey},
-        
-    },
+    ui::app::{EditorError, Shortkey},
 };
-use plugin_toolbar::toolbar::ToolbarPosition;
 
+#[cfg(feature = "ribbon")]
+use plugin_ribbon::model::ribbon_button::{RibbonButton, RibbonButtonState};
+use plugin_toolbar;
+use plugin_toolbar::toolbar::ToolbarPosition;
 use rust_editor::plugin::Plugin;
 
 #[editor_plugin(skip)]
 pub struct Redo<Data> {
     #[option(skip)]
-    stack: Vec<Rc<RefCell<dyn Action<Data>>>>,
+    _data: PhantomData<Data>,
 }
 
-impl<T> Redo<T> {
-    pub fn push<S>(&mut self, action: Rc<RefCell<S>>)
-    where
-        S: Action<T> + 'static,
-    {
-        self.stack.push(action);
-    }
-
-    pub fn push_generic(&mut self, action: Rc<RefCell<dyn Action<T>>>) {
-        self.stack.push(action);
-    }
-
-    pub fn clear(&mut self) {
-        self.stack.clear();
+fn tooltip(state: &HistoryState) -> String {
+    match (&state.next_redo, state.can_redo) {
+        (Some(action), true) => format!("Redo {}", action),
+        _ => "Nothing to redo".to_string(),
     }
 }
 
@@ -42,31 +34,65 @@ where
     fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
         editor.add_shortkey::<Redo<Data>>(vec![Key::Ctrl, Key::Y])?;
 
-        editor.plugin_mut(move |toolbar_plugin: &mut plugin_toolbar::ToolbarPlugin<Data>| {
-            
-                let toolbar = toolbar_plugin.get_or_add_toolbar("primary.undo_redo", ToolbarPosition::Left).unwrap();
+        let state = Rc::new(RefCell::new(HistoryState::default()));
+
+        let toolbar_state = Rc::clone(&state);
+        editor.plugin_mut(
+            move |toolbar_plugin: &mut plugin_toolbar::ToolbarPlugin<Data>| {
+                let toolbar = toolbar_plugin
+                    .get_or_add_toolbar("primary.undo_redo", ToolbarPosition::Left)
+                    .unwrap();
 
-                toolbar.add_toggle_button(
-                    "redo",
-                    "redo",
-                    "Redo".to_string(),
-                    || false,
-                    || EditorMessages::ShortkeyPressed(vec![Key::Ctrl, Key::Y]),
-                ).unwrap();
-        });  
+                let tooltip_state = Rc::clone(&toolbar_state);
+                let enabled_state = Rc::clone(&toolbar_state);
+                toolbar
+                    .add_conditional_button(
+                        "redo",
+                        "redo",
+                        move || tooltip(&tooltip_state.as_ref().borrow()),
+                        move || enabled_state.as_ref().borrow().can_redo,
+                        || EditorMessages::ShortkeyPressed(vec![Key::Ctrl, Key::Y]),
+                    )
+                    .unwrap();
+            },
+        );
+
+        #[cfg(feature = "ribbon")]
+        let mut ribbon_button = None;
+        #[cfg(feature = "ribbon")]
+        editor.plugin_mut(|ribbon_plugin: &mut plugin_ribbon::RibbonPlugin<Data>| {
+            let group = ribbon_plugin
+                .get_or_add_tab_mut("default", "Default")
+                .unwrap()
+                .get_or_add_group_mut("undo_redo", "History")
+                .unwrap();
+
+            let button = RibbonButton::new("redo", "redo", None, None, || {
+                EditorMessages::ShortkeyPressed(vec![Key::Ctrl, Key::Y])
+            });
+            ribbon_button = Some((Rc::clone(&button.state), Rc::clone(&button.tooltip)));
+            group.add_action(button);
+        });
+
+        editor.history_mut().subscribe(move |new_state| {
+            *state.borrow_mut() = new_state.clone();
+
+            #[cfg(feature = "ribbon")]
+            if let Some((button_state, button_tooltip)) = &ribbon_button {
+                *button_state.borrow_mut() = match new_state.can_redo {
+                    true => RibbonButtonState::Enabled,
+                    false => RibbonButtonState::Disabled,
+                };
+                *button_tooltip.borrow_mut() = Some(tooltip(new_state));
+            }
+        });
 
         Ok(())
     }
 
     fn shortkey_pressed(&mut self, key: &Shortkey, _: &Context<App<Data>>, editor: &mut App<Data>) {
         if *key == vec![Key::Ctrl, Key::Y] {
-            if let Some(action) = self.stack.pop() {
-                action.borrow_mut().redo(editor.data_mut());
-
-                editor.plugin_mut(|undo: &mut super::Undo<Data>| {
-                    undo.push_generic(Rc::clone(&action));
-                });
-            }
+            editor.redo();
         }
     }
 }
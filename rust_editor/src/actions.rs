@@ -1,4 +1,6 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 pub trait Redo<T> {
     fn redo(&mut self, map: &mut T);
@@ -21,6 +23,143 @@ pub trait Action<T>: Undo<T> + Redo<T> + Send + Sync + fmt::Display {
     fn execute(&mut self, map: &mut T) {
         self.redo(map);
     }
+
+    /// Returns the serialized form of the action. Actions are not serializable by default, opt in by implementing
+    /// [TaggedAction] and returning [SerializedAction::new] here. Make sure to register the action to the
+    /// [ActionRegistry] that is used to deserialize it again.
+    fn to_serialized(&self) -> Option<SerializedAction> {
+        None
+    }
+
+    /// Returns a description of the action that fits into a single line, e.g. for tooltips. Defaults to the
+    /// [Display](fmt::Display) text of the action.
+    fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Assigns an unique tag to an action that is used to find the matching deserializer in the [ActionRegistry].
+pub trait TaggedAction {
+    const TAG: &'static str;
+}
+
+/// An action in its serialized form, tagged with the name it is registered with.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SerializedAction {
+    pub tag: String,
+    pub value: serde_json::Value,
+}
+
+impl SerializedAction {
+    /// Serializes `action`. Returns `None` if the action cannot be represented as json.
+    pub fn new<A>(action: &A) -> Option<Self>
+    where
+        A: TaggedAction + Serialize,
+    {
+        serde_json::to_value(action)
+            .ok()
+            .map(|value| SerializedAction {
+                tag: A::TAG.to_string(),
+                value,
+            })
+    }
+}
+
+type ActionDeserializer<T> =
+    fn(&ActionRegistry<T>, serde_json::Value) -> Result<Box<dyn Action<T>>, serde_json::Error>;
+
+fn deserialize_action<T, A>(
+    _: &ActionRegistry<T>,
+    value: serde_json::Value,
+) -> Result<Box<dyn Action<T>>, serde_json::Error>
+where
+    A: Action<T> + DeserializeOwned + 'static,
+{
+    Ok(Box::new(serde_json::from_value::<A>(value)?))
+}
+
+fn deserialize_multi_action<T>(
+    registry: &ActionRegistry<T>,
+    value: serde_json::Value,
+) -> Result<Box<dyn Action<T>>, serde_json::Error>
+where
+    T: 'static,
+{
+    let mut multi_action = MultiAction::new();
+    for action in serde_json::from_value::<Vec<SerializedAction>>(value)? {
+        multi_action.actions.push(registry.deserialize(action)?);
+    }
+
+    Ok(Box::new(multi_action))
+}
+
+/// Maps the tags of all serializable actions to functions that restore them from their serialized form.
+///
+/// [MultiAction] is registered by default and serializable as long as all of its actions are.
+///
+/// # Example
+///
+/// ```
+/// let mut registry = ActionRegistry::<Map>::new();
+/// registry.register::<CreateStreet>();
+///
+/// let action = registry.deserialize(serialized)?;
+/// ```
+pub struct ActionRegistry<T> {
+    deserializers: HashMap<&'static str, ActionDeserializer<T>>,
+}
+
+impl<T> Default for ActionRegistry<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ActionRegistry<T>
+where
+    T: 'static,
+{
+    pub fn new() -> Self {
+        let mut registry = ActionRegistry {
+            deserializers: HashMap::new(),
+        };
+        registry
+            .deserializers
+            .insert(MultiAction::<T>::TAG, deserialize_multi_action::<T>);
+
+        registry
+    }
+
+    /// Registers the action type `A`. Registering another action with the same tag replaces the previous one.
+    pub fn register<A>(&mut self)
+    where
+        A: Action<T> + TaggedAction + DeserializeOwned + 'static,
+    {
+        self.deserializers.insert(A::TAG, deserialize_action::<T, A>);
+    }
+
+    /// Returns true if an action with `tag` is registered.
+    pub fn is_registered(&self, tag: &str) -> bool {
+        self.deserializers.contains_key(tag)
+    }
+
+    /// Restores an action from its serialized form.
+    ///
+    /// # Errors
+    ///
+    /// Fails if no action is registered with the tag of `action` or if the value does not match the registered type.
+    pub fn deserialize(&self, action: SerializedAction) -> Result<Box<dyn Action<T>>, serde_json::Error> {
+        match self.deserializers.get(&action.tag[..]) {
+            Some(deserializer) => deserializer(self, action.value),
+            None => Err(serde::de::Error::custom(format!(
+                "no action is registered with the tag \"{}\"",
+                action.tag
+            ))),
+        }
+    }
 }
 
 pub struct MultiAction<T> {
@@ -51,7 +190,34 @@ impl<T> MultiAction<T> {
     }
 }
 
-impl<T> Action<T> for MultiAction<T> {}
+impl<T> TaggedAction for MultiAction<T> {
+    const TAG: &'static str = "multi_action";
+}
+
+impl<T> Action<T> for MultiAction<T> {
+    fn to_serialized(&self) -> Option<SerializedAction> {
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| action.to_serialized())
+            .collect::<Option<Vec<SerializedAction>>>()?;
+
+        Some(SerializedAction {
+            tag: Self::TAG.to_string(),
+            value: serde_json::to_value(actions).ok()?,
+        })
+    }
+
+    /// Names the first action and counts the others, the [Display](fmt::Display) text lists every action on its own
+    /// line.
+    fn summary(&self) -> String {
+        match self.actions.split_first() {
+            Some((first, [])) => first.summary(),
+            Some((first, others)) => format!("{} and {} more", first.summary(), others.len()),
+            None => String::new(),
+        }
+    }
+}
 
 impl<T> Redo<T> for MultiAction<T> {
     fn redo(&mut self, map: &mut T) {
@@ -59,6 +225,10 @@ impl<T> Redo<T> for MultiAction<T> {
             (*action).redo(map);
         }
     }
+
+    fn is_redoable(&self) -> bool {
+        self.actions.iter().all(|action| action.is_redoable())
+    }
 }
 
 impl<T> Undo<T> for MultiAction<T> {
@@ -67,4 +237,133 @@ impl<T> Undo<T> for MultiAction<T> {
             (*action).undo(map);
         }
     }
+
+    fn is_undoable(&self) -> bool {
+        self.actions.iter().all(|action| action.is_undoable())
+    }
+}
+
+impl<T, A> Redo<T> for Box<A>
+where
+    A: Redo<T> + ?Sized,
+{
+    fn redo(&mut self, map: &mut T) {
+        (**self).redo(map);
+    }
+
+    fn is_redoable(&self) -> bool {
+        (**self).is_redoable()
+    }
+}
+
+impl<T, A> Undo<T> for Box<A>
+where
+    A: Undo<T> + ?Sized,
+{
+    fn undo(&mut self, map: &mut T) {
+        (**self).undo(map);
+    }
+
+    fn is_undoable(&self) -> bool {
+        (**self).is_undoable()
+    }
+}
+
+/// Allows to store deserialized actions wherever a sized action is expected, e.g. `Rc<RefCell<dyn Action<T>>>`.
+impl<T> Action<T> for Box<dyn Action<T>> {
+    fn execute(&mut self, map: &mut T) {
+        (**self).execute(map);
+    }
+
+    fn to_serialized(&self) -> Option<SerializedAction> {
+        (**self).to_serialized()
+    }
+
+    fn summary(&self) -> String {
+        (**self).summary()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{Action, ActionRegistry, MultiAction, Redo, SerializedAction, TaggedAction, Undo};
+
+    #[derive(Serialize, Deserialize)]
+    struct Add {
+        value: i32,
+    }
+
+    impl fmt::Display for Add {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Add {}", self.value)
+        }
+    }
+
+    impl TaggedAction for Add {
+        const TAG: &'static str = "add";
+    }
+
+    impl Undo<i32> for Add {
+        fn undo(&mut self, map: &mut i32) {
+            *map -= self.value;
+        }
+    }
+
+    impl Redo<i32> for Add {
+        fn redo(&mut self, map: &mut i32) {
+            *map += self.value;
+        }
+    }
+
+    impl Action<i32> for Add {
+        fn to_serialized(&self) -> Option<SerializedAction> {
+            SerializedAction::new(self)
+        }
+    }
+
+    #[test]
+    fn deserialize_registered_action() {
+        let mut registry = ActionRegistry::<i32>::new();
+        registry.register::<Add>();
+
+        let serialized = Add { value: 4 }.to_serialized().unwrap();
+        let mut action = registry.deserialize(serialized).unwrap();
+
+        let mut data = 1;
+        action.redo(&mut data);
+        assert_eq!(data, 5);
+    }
+
+    #[test]
+    fn deserialize_multi_action() {
+        let mut registry = ActionRegistry::<i32>::new();
+        registry.register::<Add>();
+
+        let mut multi_action = MultiAction::new();
+        multi_action.push(Add { value: 2 });
+        multi_action.push(Add { value: 3 });
+
+        let serialized = multi_action.to_serialized().unwrap();
+        let mut action = registry.deserialize(serialized).unwrap();
+        assert_eq!(action.summary(), "Add 2 and 1 more");
+
+        let mut data = 0;
+        action.redo(&mut data);
+        assert_eq!(data, 5);
+
+        action.undo(&mut data);
+        assert_eq!(data, 0);
+    }
+
+    #[test]
+    fn unregistered_action_fails() {
+        let registry = ActionRegistry::<i32>::new();
+        let serialized = Add { value: 4 }.to_serialized().unwrap();
+
+        assert!(registry.deserialize(serialized).is_err());
+    }
 }
@@ -0,0 +1,207 @@
+use std::{error::Error, rc::Rc};
+
+use geo::Rect;
+use gloo::timers::callback::Timeout;
+use js_sys::{Array, Uint8Array};
+use thiserror::Error;
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+use crate::ui::app::App;
+
+pub mod svg;
+
+/// Name of the built-in exporter that saves the canvas as png image
+pub const PNG_EXPORTER: &str = "png";
+
+/// Name of the exporter added with [App::register_json_exporter]
+pub const JSON_EXPORTER: &str = "json";
+
+/// Name of the exporter added with [App::register_svg_exporter]
+pub const SVG_EXPORTER: &str = "svg";
+
+/// Describes an exporter, used to list it in menus.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExporterInfo {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub mime_type: &'static str,
+
+    /// Extension of the exported file without the dot
+    pub extension: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// Part of the canvas that is exported in canvas pixels, the whole canvas if `None`. Exporters that do not export
+    /// the canvas ignore it.
+    pub region: Option<Rect<f64>>,
+
+    /// Factor the size of the exported image is multiplied by
+    pub scale: f64,
+
+    /// Name of the exported file without the extension
+    pub file_name: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            region: None,
+            scale: 1.0,
+            file_name: String::from("export"),
+        }
+    }
+}
+
+/// Result of an exporter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportedFile {
+    pub name: String,
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("no exporter named {name:?} is registered")]
+    Unknown { name: String },
+
+    #[error("export as {name} failed: {source}")]
+    Failed {
+        name: &'static str,
+        source: Box<dyn Error>,
+    },
+
+    #[error("could not download {file}: {reason}")]
+    Download { file: String, reason: String },
+}
+
+/// Describes the outcome of an export, see [App::listen_exports].
+pub enum ExportEvent<'a> {
+    Exported {
+        exporter: &'a str,
+        file: &'a ExportedFile,
+    },
+    Failed(&'a ExportError),
+}
+
+/// Converts the data or the canvas of the editor into the content of a file.
+pub type Exporter<Data> = Rc<dyn Fn(&App<Data>, &ExportOptions) -> Result<Vec<u8>, Box<dyn Error>>>;
+
+struct Registration<Data>
+where
+    Data: Default + 'static,
+{
+    info: ExporterInfo,
+    exporter: Exporter<Data>,
+}
+
+type Listener = Box<dyn Fn(&ExportEvent)>;
+
+/// Knows all exporters and what happens to the exported files, see [App::register_exporter].
+pub struct ExportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    registrations: Vec<Registration<Data>>,
+    handler: Option<Rc<dyn Fn(ExportedFile)>>,
+    listeners: Vec<Listener>,
+}
+
+impl<Data> Default for ExportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    fn default() -> Self {
+        ExportRegistry {
+            registrations: Vec::new(),
+            handler: None,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl<Data> ExportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    /// Registers `exporter` under the name of `info`, replacing an exporter of the same name.
+    pub fn register(&mut self, info: ExporterInfo, exporter: Exporter<Data>) {
+        self.registrations
+            .retain(|registration| registration.info.name != info.name);
+        self.registrations.push(Registration { info, exporter });
+    }
+
+    /// Returns all exporters in the order they were registered.
+    pub fn exporters(&self) -> Vec<ExporterInfo> {
+        self.registrations
+            .iter()
+            .map(|registration| registration.info.clone())
+            .collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<(ExporterInfo, Exporter<Data>)> {
+        self.registrations
+            .iter()
+            .find(|registration| registration.info.name == name)
+            .map(|registration| (registration.info.clone(), Rc::clone(&registration.exporter)))
+    }
+
+    /// Hands exported files to `handler` instead of downloading them. Allows an application that embeds the editor to
+    /// decide where exported files go.
+    pub fn set_handler(&mut self, handler: impl Fn(ExportedFile) + 'static) {
+        self.handler = Some(Rc::new(handler));
+    }
+
+    pub fn handler(&self) -> Option<Rc<dyn Fn(ExportedFile)>> {
+        self.handler.clone()
+    }
+
+    pub fn listen(&mut self, listener: impl Fn(&ExportEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    pub(crate) fn emit(&self, event: &ExportEvent) {
+        for listener in &self.listeners {
+            listener(event);
+        }
+    }
+}
+
+/// Milliseconds the object url of a downloaded file is kept
+const DOWNLOAD_URL_LIFETIME: u32 = 60_000;
+
+/// Lets the browser download `file`.
+pub fn download(file: &ExportedFile) -> Result<(), ExportError> {
+    let error = |reason: String| ExportError::Download {
+        file: file.name.clone(),
+        reason,
+    };
+
+    let parts = Array::of1(&Uint8Array::from(file.bytes.as_slice()));
+    let properties = BlobPropertyBag::new();
+    properties.set_type(&file.mime_type);
+
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &properties)
+        .map_err(|e| error(format!("{:?}", e)))?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(|e| error(format!("{:?}", e)))?;
+
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<HtmlAnchorElement>().ok())
+        .ok_or_else(|| error(String::from("no document")))?;
+
+    anchor.set_href(&url);
+    anchor.set_download(&file.name);
+    anchor.click();
+
+    // Some browsers read the url only after the click was handled, so it is revoked once the download surely started
+    Timeout::new(DOWNLOAD_URL_LIFETIME, move || {
+        let _ = Url::revoke_object_url(&url);
+    })
+    .forget();
+
+    Ok(())
+}
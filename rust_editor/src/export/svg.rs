@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use geo::{Coordinate, Geometry, LineString, Polygon, Rect};
+
+use crate::style::Style;
+
+/// Radius of points, matches the size points are rendered with on the canvas
+const POINT_RADIUS: f64 = 5.0;
+
+/// Writes `shapes` as svg document. Only the part within `view_box` is visible, the size of the document is the size of
+/// `view_box` multiplied by `scale`.
+///
+/// Shapes are written with the same interpretation of their [Style] as the canvas renderer uses, so the document looks
+/// like the rendered canvas.
+pub fn document(shapes: &[(Geometry<f64>, Style)], view_box: Rect<f64>, scale: f64) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        view_box.width() * scale,
+        view_box.height() * scale,
+        view_box.min().x,
+        view_box.min().y,
+        view_box.width(),
+        view_box.height(),
+    );
+
+    for (geometry, style) in shapes {
+        write_geometry(&mut svg, geometry, style);
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn write_geometry(svg: &mut String, geometry: &Geometry<f64>, style: &Style) {
+    match geometry {
+        Geometry::Point(point) => write_point(svg, point.0, style),
+        Geometry::MultiPoint(points) => {
+            for point in points {
+                write_point(svg, point.0, style);
+            }
+        }
+        Geometry::Line(line) => {
+            let _ = write!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}"/>"#,
+                line.start.x,
+                line.start.y,
+                line.end.x,
+                line.end.y,
+                escape(&style.background_color),
+                style.border_width.max(1)
+            );
+        }
+        // Line strings are closed and filled on the canvas as well
+        Geometry::LineString(line_string) => write_path(svg, &ring(line_string), style),
+        Geometry::MultiLineString(line_strings) => {
+            for line_string in line_strings {
+                write_path(svg, &ring(line_string), style);
+            }
+        }
+        Geometry::Polygon(polygon) => write_path(svg, &polygon_path(polygon), style),
+        Geometry::MultiPolygon(polygons) => {
+            for polygon in polygons {
+                write_path(svg, &polygon_path(polygon), style);
+            }
+        }
+        Geometry::Rect(rect) => write_path(svg, &ring(&rect.to_polygon().into_inner().0), style),
+        Geometry::Triangle(triangle) => {
+            write_path(svg, &ring(&triangle.to_polygon().into_inner().0), style)
+        }
+        Geometry::GeometryCollection(collection) => {
+            for geometry in collection {
+                write_geometry(svg, geometry, style);
+            }
+        }
+    }
+}
+
+fn write_point(svg: &mut String, point: Coordinate<f64>, style: &Style) {
+    let _ = write!(
+        svg,
+        r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+        point.x,
+        point.y,
+        POINT_RADIUS,
+        paint(style)
+    );
+}
+
+fn write_path(svg: &mut String, path: &str, style: &Style) {
+    if !path.is_empty() {
+        let _ = write!(
+            svg,
+            r#"<path d="{}" fill-rule="evenodd" {}/>"#,
+            path,
+            paint(style)
+        );
+    }
+}
+
+/// Returns the fill and stroke attributes for `style`.
+fn paint(style: &Style) -> String {
+    match style.border_width {
+        0 => format!(r#"fill="{}""#, escape(&style.background_color)),
+        width => format!(
+            r#"fill="{}" stroke="{}" stroke-width="{}""#,
+            escape(&style.background_color),
+            escape(&style.border_color),
+            width
+        ),
+    }
+}
+
+/// Returns the path data of a closed ring through all points of `line_string`.
+fn ring(line_string: &LineString<f64>) -> String {
+    let mut path = String::new();
+
+    for (i, point) in line_string.0.iter().enumerate() {
+        let command = if i == 0 { 'M' } else { 'L' };
+        let _ = write!(path, "{}{} {} ", command, point.x, point.y);
+    }
+
+    if !path.is_empty() {
+        path.push('Z');
+    }
+
+    path
+}
+
+/// Interiors are added as further rings, which the even odd fill rule cuts out of the exterior.
+fn polygon_path(polygon: &Polygon<f64>) -> String {
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(ring)
+        .filter(|ring| !ring.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Coordinate, Geometry, Line, LineString, Polygon, Rect};
+
+    use super::document;
+    use crate::style::Style;
+
+    #[test]
+    fn shapes_are_written_as_svg() {
+        let style = Style {
+            border_width: 0,
+            border_color: String::new(),
+            background_color: String::from("#FF0000"),
+            text_color: String::new(),
+        };
+
+        let shapes = vec![
+            (
+                Geometry::Polygon(Polygon::new(
+                    LineString::from(vec![(0., 0.), (2., 0.), (2., 1.)]),
+                    vec![],
+                )),
+                style.clone(),
+            ),
+            (
+                Geometry::Line(Line::new(
+                    Coordinate { x: 0., y: 0. },
+                    Coordinate { x: 4., y: 4. },
+                )),
+                style,
+            ),
+        ];
+
+        let svg = document(
+            &shapes,
+            Rect::new(Coordinate { x: 0., y: 0. }, Coordinate { x: 10., y: 5. }),
+            2.,
+        );
+
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10" viewBox="0 0 10 5">"#
+        ));
+        assert!(svg
+            .contains(r##"<path d="M0 0 L2 0 L2 1 L0 0 Z" fill-rule="evenodd" fill="#FF0000"/>"##));
+        assert!(svg.contains(
+            r##"<line x1="0" y1="0" x2="4" y2="4" stroke="#FF0000" stroke-width="1"/>"##
+        ));
+        assert!(svg.ends_with("</svg>"));
+    }
+}
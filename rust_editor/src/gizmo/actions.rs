@@ -0,0 +1,260 @@
+use std::{fmt, marker::PhantomData};
+
+use geo::Coordinate;
+use uuid::Uuid;
+
+use super::{Scale, SetPosition, SetRotation};
+use crate::actions::{Action, Redo, Undo};
+
+/// Data that holds the elements transformed by gizmos. Allows the actions emitted by gizmos to be undone and redone.
+pub trait ElementsById<T> {
+    fn element_by_id_mut(&mut self, id: &Uuid) -> Option<&mut T>;
+}
+
+/// Emitted by the [MoveGizmo](super::MoveGizmo) once a drag ended.
+pub struct MoveAction<T> {
+    /// Id, position before and position after the drag of each moved element
+    positions: Vec<(Uuid, Coordinate<f64>, Coordinate<f64>)>,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T> MoveAction<T> {
+    pub fn new(positions: Vec<(Uuid, Coordinate<f64>, Coordinate<f64>)>) -> Self {
+        MoveAction {
+            positions,
+            _element: PhantomData,
+        }
+    }
+
+    pub fn positions(&self) -> &[(Uuid, Coordinate<f64>, Coordinate<f64>)] {
+        &self.positions
+    }
+}
+
+impl<Data, T> Undo<Data> for MoveAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition,
+{
+    fn undo(&mut self, data: &mut Data) {
+        for (id, before, _) in &self.positions {
+            if let Some(element) = data.element_by_id_mut(id) {
+                element.set_position(*before);
+            }
+        }
+    }
+}
+
+impl<Data, T> Redo<Data> for MoveAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition,
+{
+    fn redo(&mut self, data: &mut Data) {
+        for (id, _, after) in &self.positions {
+            if let Some(element) = data.element_by_id_mut(id) {
+                element.set_position(*after);
+            }
+        }
+    }
+}
+
+impl<Data, T> Action<Data> for MoveAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition,
+{
+}
+
+impl<T> fmt::Display for MoveAction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move {} elements", self.positions.len())
+    }
+}
+
+/// Emitted by the [RotateGizmo](super::RotateGizmo) once a drag ended.
+pub struct RotateAction<T> {
+    /// Id, position and rotation before and position and rotation after the drag of each rotated element
+    states: Vec<(Uuid, (Coordinate<f64>, f64), (Coordinate<f64>, f64))>,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T> RotateAction<T> {
+    pub fn new(states: Vec<(Uuid, (Coordinate<f64>, f64), (Coordinate<f64>, f64))>) -> Self {
+        RotateAction {
+            states,
+            _element: PhantomData,
+        }
+    }
+
+    fn apply<Data>(data: &mut Data, id: &Uuid, (position, rotation): (Coordinate<f64>, f64))
+    where
+        Data: ElementsById<T>,
+        T: SetPosition + SetRotation,
+    {
+        if let Some(element) = data.element_by_id_mut(id) {
+            element.set_position(position);
+            element.set_rotation(rotation);
+        }
+    }
+}
+
+impl<Data, T> Undo<Data> for RotateAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition + SetRotation,
+{
+    fn undo(&mut self, data: &mut Data) {
+        for (id, before, _) in &self.states {
+            Self::apply(data, id, *before);
+        }
+    }
+}
+
+impl<Data, T> Redo<Data> for RotateAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition + SetRotation,
+{
+    fn redo(&mut self, data: &mut Data) {
+        for (id, _, after) in &self.states {
+            Self::apply(data, id, *after);
+        }
+    }
+}
+
+impl<Data, T> Action<Data> for RotateAction<T>
+where
+    Data: ElementsById<T>,
+    T: SetPosition + SetRotation,
+{
+}
+
+impl<T> fmt::Display for RotateAction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rotate {} elements", self.states.len())
+    }
+}
+
+/// Emitted by the [ScaleGizmo](super::ScaleGizmo) once a drag ended.
+///
+/// Since [Scale] only scales relative to the current size, the action stores the applied factor and undoes it by
+/// scaling with the inverse factor.
+pub struct ScaleAction<T> {
+    ids: Vec<Uuid>,
+    factor: Coordinate<f64>,
+    origin: Coordinate<f64>,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T> ScaleAction<T> {
+    pub fn new(ids: Vec<Uuid>, factor: Coordinate<f64>, origin: Coordinate<f64>) -> Self {
+        ScaleAction {
+            ids,
+            factor,
+            origin,
+            _element: PhantomData,
+        }
+    }
+
+    pub fn factor(&self) -> Coordinate<f64> {
+        self.factor
+    }
+
+    fn apply<Data>(&self, data: &mut Data, factor: Coordinate<f64>)
+    where
+        Data: ElementsById<T>,
+        T: Scale,
+    {
+        for id in &self.ids {
+            if let Some(element) = data.element_by_id_mut(id) {
+                element.scale(factor, self.origin);
+            }
+        }
+    }
+}
+
+impl<Data, T> Undo<Data> for ScaleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Scale,
+{
+    fn undo(&mut self, data: &mut Data) {
+        let inverse = Coordinate {
+            x: 1. / self.factor.x,
+            y: 1. / self.factor.y,
+        };
+        self.apply(data, inverse);
+    }
+}
+
+impl<Data, T> Redo<Data> for ScaleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Scale,
+{
+    fn redo(&mut self, data: &mut Data) {
+        self.apply(data, self.factor);
+    }
+}
+
+impl<Data, T> Action<Data> for ScaleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Scale,
+{
+}
+
+impl<T> fmt::Display for ScaleAction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Scale {} elements by {:.2} x {:.2}",
+            self.ids.len(),
+            self.factor.x,
+            self.factor.y
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::Coordinate;
+
+    use crate::{
+        actions::{Redo, Undo},
+        gizmo::{test_element::Element, Gizmo, MoveGizmo, SetPosition},
+    };
+
+    #[test]
+    fn finished_drags_can_be_undone() {
+        let start = Coordinate { x: 10., y: 20. };
+        let mut elements = vec![Element::at(start)];
+
+        let mut gizmo = MoveGizmo::new();
+        gizmo.set_position(start);
+
+        // A click without moving changes nothing
+        gizmo.mouse_down(start, 0, elements.iter_mut());
+        assert!(gizmo.mouse_up(start, 0, elements.iter_mut()).is_none());
+
+        gizmo.mouse_down(start, 0, elements.iter_mut());
+        Gizmo::<Element>::mouse_move(
+            &mut gizmo,
+            Coordinate { x: 50., y: 30. },
+            elements.iter_mut(),
+        );
+        let mut action = gizmo
+            .mouse_up(Coordinate { x: 50., y: 30. }, 0, elements.iter_mut())
+            .expect("a drag should emit an action");
+
+        let end = Coordinate { x: 50., y: 30. };
+        assert_eq!(action.positions(), &[(elements[0].id, start, end)]);
+
+        action.undo(&mut elements);
+        assert_eq!(elements[0].position, start);
+
+        action.redo(&mut elements);
+        assert_eq!(elements[0].position, end);
+    }
+}
@@ -0,0 +1,716 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use geo::{prelude::EuclideanDistance, Coordinate, Line, Point, Rect, Triangle};
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::{
+    renderer::PrimitiveRenderer,
+    snapping::{SnapHint, Snapping},
+    style::{InteractiveElementStyle, Style},
+};
+
+mod actions;
+mod numeric;
+mod rotate;
+mod scale;
+
+pub use self::actions::{ElementsById, MoveAction, RotateAction, ScaleAction};
+pub use self::numeric::{NumericEvent, NumericInput, NumericValue};
+pub use self::rotate::{rotate_around, RotateGizmo};
+pub use self::scale::{Scale, ScaleGizmo};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    XY,
+}
+
+pub trait GetPosition {
+    fn position(&self) -> Coordinate<f64>;
+}
+
+pub trait SetPosition {
+    fn set_position(&mut self, position: Coordinate<f64>);
+}
+
+pub trait GetRotation {
+    /// Rotation in radians, clockwise on screen
+    fn rotation(&self) -> f64;
+}
+
+pub trait SetRotation {
+    fn set_rotation(&mut self, rotation: f64);
+}
+
+/// Modifier keys that change how a gizmo behaves during a drag, e.g. Shift to snap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+pub trait Id {
+    fn id(&self) -> Uuid;
+}
+
+pub trait SetId {
+    fn set_id(&mut self, id: Uuid);
+}
+
+pub trait Gizmo<'a, T: 'a>: GetPosition + SetPosition
+where
+    T: GetPosition + SetPosition,
+{
+    /// Describes a finished drag, see [mouse_up](Gizmo::mouse_up)
+    type Action;
+
+    /// Starts a drag if `mouse_pos` is on a handle and remembers the state of `elements` before the drag.
+    fn mouse_down(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    );
+    fn mouse_move(&mut self, mouse_pos: Coordinate<f64>, elements: impl Iterator<Item = &'a mut T>);
+
+    /// Ends the drag. Returns an action holding the state of the dragged elements before and after the drag, which
+    /// is added to the history with [App::finish_interaction](crate::ui::app::App::finish_interaction). Returns
+    /// `None` if no drag was active or nothing changed.
+    fn mouse_up(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) -> Option<Self::Action>;
+
+    /// Sets the modifier keys that are currently pressed. Gizmos that do not use modifiers ignore them.
+    fn set_modifiers(&mut self, _modifiers: Modifiers) {}
+
+    /// Transforms the elements of the active drag by a typed value instead of the cursor position, see
+    /// [NumericInput]. Does nothing if no drag is active.
+    fn set_value(&mut self, _value: NumericValue, _elements: impl Iterator<Item = &'a mut T>) {}
+
+    /// Aborts the active drag and restores the state the elements had before it.
+    fn cancel(&mut self, _elements: impl Iterator<Item = &'a mut T>) {}
+
+    /// Renders the gizmo, meant to be called from [Plugin::render_overlay](crate::plugin::Plugin::render_overlay).
+    fn render(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Result<(), JsValue>;
+}
+
+/// Returns the number of screen pixels per world unit of the current transform of `context`, i.e. the zoom of the
+/// camera. Gizmos divide their sizes by it so that they keep the same size on screen at every zoom level.
+pub fn view_scale(context: &CanvasRenderingContext2d) -> f64 {
+    match context.get_transform() {
+        Ok(matrix) => {
+            let scale = matrix.a().hypot(matrix.b());
+            match scale > 0. {
+                true => scale,
+                false => 1.,
+            }
+        }
+        Err(_) => 1.,
+    }
+}
+
+/// Runs `draw` with a transform that places the origin at the world coordinate `position` and measures in screen
+/// pixels, so that everything `draw` renders has the same size at every zoom level. The transform of the camera is
+/// restored afterwards.
+pub fn draw_in_screen_space(
+    context: &CanvasRenderingContext2d,
+    position: Coordinate<f64>,
+    draw: impl FnOnce() -> Result<(), JsValue>,
+) -> Result<(), JsValue> {
+    let matrix = context.get_transform()?;
+    let x = matrix.a() * position.x + matrix.c() * position.y + matrix.e();
+    let y = matrix.b() * position.x + matrix.d() * position.y + matrix.f();
+
+    context.save();
+    let result = context
+        .set_transform(1., 0., 0., 1., x, y)
+        .and_then(|_| draw());
+    context.restore();
+
+    result
+}
+
+impl GetPosition for MoveGizmo {
+    fn position(&self) -> Coordinate<f64> {
+        self.position
+    }
+}
+
+impl SetPosition for MoveGizmo {
+    fn set_position(&mut self, position: Coordinate<f64>) {
+        self.position = position;
+    }
+}
+
+/// Returns true if `mouse_pos` is on one of the handles of a [MoveGizmo] at `origin`.
+pub fn mouse_over(mouse_pos: Coordinate<f64>, origin: Coordinate<f64>) -> bool {
+    let mut gizmo = MoveGizmo::new();
+    gizmo.set_position(origin);
+
+    gizmo.mouse_over(mouse_pos)
+}
+
+impl<'a, T: GetPosition + SetPosition + Id + 'a> Gizmo<'a, T> for MoveGizmo {
+    type Action = MoveAction<T>;
+
+    fn mouse_down(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        _button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        self.affected_axis = self.axis_at(mouse_pos);
+        self.active = self.affected_axis.is_some();
+        if !self.active {
+            return;
+        }
+
+        self.cursor_to_element_offset = mouse_pos - self.position();
+        self.start_position = self.position();
+        self.offsets.clear();
+        self.starts.clear();
+        for x in elements {
+            self.offsets.insert(x.id(), self.position() - x.position());
+            self.starts.insert(x.id(), x.position());
+        }
+    }
+
+    fn mouse_move(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        let axis = match (self.active, self.affected_axis) {
+            (true, Some(axis)) => axis,
+            _ => {
+                self.hovered_axis = self.axis_at(mouse_pos);
+                return;
+            }
+        };
+
+        let position = self.position();
+        let constrain = |target: Coordinate<f64>| match axis {
+            Axis::X => Coordinate {
+                x: target.x,
+                y: position.y,
+            },
+            Axis::Y => Coordinate {
+                x: position.x,
+                y: target.y,
+            },
+            Axis::XY => target,
+        };
+
+        let mut new_position = constrain(mouse_pos - self.cursor_to_element_offset);
+        self.snap_hint = None;
+        if let Some(snapping) = &self.snapping {
+            let snapped = snapping
+                .borrow()
+                .snap_at_scale(new_position, self.view_scale.get());
+            let snapped_position = constrain(snapped.point);
+
+            // Geometry beside the locked axis still moves the gizmo along it, but is not marked as snapped to
+            if snapped_position == snapped.point {
+                self.snap_hint = snapped.hint;
+            }
+            new_position = snapped_position;
+        }
+
+        // Elements added since the drag began are not moved
+        for x in elements {
+            if let Some(offset) = self.offsets.get(&x.id()) {
+                let old_position = x.position();
+                x.set_position(match axis {
+                    Axis::X => Coordinate {
+                        x: new_position.x - offset.x,
+                        y: old_position.y,
+                    },
+                    Axis::Y => Coordinate {
+                        x: old_position.x,
+                        y: new_position.y - offset.y,
+                    },
+                    Axis::XY => new_position - *offset,
+                });
+            }
+        }
+
+        self.set_position(new_position);
+    }
+
+    fn mouse_up(
+        &mut self,
+        _mouse_pos: Coordinate<f64>,
+        _button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) -> Option<MoveAction<T>> {
+        let was_active = self.active;
+        self.active = false;
+        self.affected_axis = None;
+        self.snap_hint = None;
+
+        let starts = std::mem::take(&mut self.starts);
+        if !was_active {
+            return None;
+        }
+
+        let positions: Vec<_> = elements
+            .filter_map(|x| {
+                let start = *starts.get(&x.id())?;
+                Some((x.id(), start, x.position()))
+            })
+            .filter(|(_, start, end)| start != end)
+            .collect();
+
+        match positions.is_empty() {
+            true => None,
+            false => Some(MoveAction::new(positions)),
+        }
+    }
+
+    /// Moves the elements by the typed value along its axis. Without an axis the value applies to the dragged axis or,
+    /// if the handle at the origin is dragged, to both axes.
+    fn set_value(&mut self, value: NumericValue, elements: impl Iterator<Item = &'a mut T>) {
+        let axis = match (self.active, value.axis.or(self.affected_axis)) {
+            (true, Some(axis)) => axis,
+            _ => return,
+        };
+
+        let offset = match axis {
+            Axis::X => Coordinate {
+                x: value.value,
+                y: 0.,
+            },
+            Axis::Y => Coordinate {
+                x: 0.,
+                y: value.value,
+            },
+            Axis::XY => Coordinate {
+                x: value.value,
+                y: value.value,
+            },
+        };
+
+        for x in elements {
+            if let Some(start) = self.starts.get(&x.id()) {
+                x.set_position(*start + offset);
+            }
+        }
+
+        self.snap_hint = None;
+        self.set_position(self.start_position + offset);
+    }
+
+    fn cancel(&mut self, elements: impl Iterator<Item = &'a mut T>) {
+        if !self.active {
+            return;
+        }
+
+        for x in elements {
+            if let Some(start) = self.starts.get(&x.id()) {
+                x.set_position(*start);
+            }
+        }
+
+        self.set_position(self.start_position);
+        self.active = false;
+        self.affected_axis = None;
+        self.snap_hint = None;
+        self.starts.clear();
+    }
+
+    fn render(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Result<(), JsValue> {
+        if elements.peekable().peek().is_some() {
+            self.view_scale.set(view_scale(context));
+
+            draw_in_screen_space(context, self.position(), || {
+                self.x_handle.render(self.handle_style(Axis::X), context)?;
+                self.y_handle.render(self.handle_style(Axis::Y), context)?;
+
+                context.begin_path();
+                center_handle().render(self.handle_style(Axis::XY), context)
+            })?;
+
+            if let Some(hint) = &self.snap_hint {
+                hint.render(context)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Sizes of the handles are in screen pixels
+
+static ARROW_WIDTH: f64 = 6.0;
+static ARROW_HEIGHT: f64 = 10.0;
+static LINE_LENGTH: f64 = 100.0;
+
+/// Width and height of the handle at the origin that moves along both axes
+static CENTER_SIZE: f64 = 12.0;
+
+/// Distance from a handle within which the handle can still be grabbed
+static HANDLE_TOLERANCE: f64 = 5.0;
+
+/// The handle at the origin of the gizmo, relative to the origin
+fn center_handle() -> Rect<f64> {
+    let half = CENTER_SIZE / 2.;
+    Rect::new(
+        Coordinate { x: -half, y: -half },
+        Coordinate { x: half, y: half },
+    )
+}
+
+struct GizmoArrow {
+    line: Line<f64>,
+    arrow: Triangle<f64>,
+}
+
+impl GizmoArrow {
+    pub fn new(line_end: Coordinate<f64>) -> Self {
+        let len = line_end.euclidean_distance(&Coordinate { x: 0., y: 0. });
+        let norm = Coordinate {
+            x: line_end.x / len,
+            y: line_end.y / len,
+        };
+        let perp = Coordinate {
+            x: -norm.y,
+            y: norm.x,
+        };
+
+        GizmoArrow {
+            line: Line::new(Coordinate { x: 0., y: 0. }, line_end),
+            arrow: Triangle(
+                norm * (len - ARROW_HEIGHT) - perp * ARROW_WIDTH / 2.,
+                line_end,
+                norm * (len - ARROW_HEIGHT) + perp * ARROW_WIDTH / 2.,
+            ),
+        }
+    }
+
+    /// Returns true if `point`, relative to the origin of the gizmo, is on the line or the arrow head.
+    pub fn hit(&self, point: Coordinate<f64>) -> bool {
+        let point = Point::from(point);
+
+        point.euclidean_distance(&self.line) <= HANDLE_TOLERANCE
+            || point.euclidean_distance(&self.arrow.to_polygon()) <= HANDLE_TOLERANCE
+    }
+
+    pub fn render(
+        &self,
+        style: &Style,
+        context: &web_sys::CanvasRenderingContext2d,
+    ) -> Result<(), JsValue> {
+        self.line.render(style, context)?;
+        self.arrow.render(style, context)?;
+
+        Ok(())
+    }
+}
+
+pub struct MoveGizmo {
+    position: Coordinate<f64>,
+
+    x_handle: GizmoArrow,
+    y_handle: GizmoArrow,
+    style: InteractiveElementStyle,
+
+    cursor_to_element_offset: Coordinate<f64>,
+
+    offsets: HashMap<Uuid, Coordinate<f64>>,
+
+    /// Position of each element when the drag started
+    starts: HashMap<Uuid, Coordinate<f64>>,
+
+    /// Position of the gizmo when the drag started
+    start_position: Coordinate<f64>,
+
+    active: bool,
+
+    /// Axis the current drag moves along, `None` if no handle is dragged
+    pub affected_axis: Option<Axis>,
+
+    /// Axis of the handle below the cursor, `None` if the cursor is not on a handle
+    hovered_axis: Option<Axis>,
+
+    snapping: Option<Rc<RefCell<Snapping>>>,
+
+    /// What the gizmo snapped to during the current drag
+    snap_hint: Option<SnapHint>,
+
+    /// Zoom of the camera when the gizmo was rendered last, used to hit-test the handles at their size on screen
+    view_scale: Cell<f64>,
+}
+
+impl Default for MoveGizmo {
+    fn default() -> Self {
+        MoveGizmo::new()
+    }
+}
+
+impl MoveGizmo {
+    pub fn new() -> Self {
+        let style = |color: &str| Style {
+            border_width: 2,
+            border_color: color.to_string(),
+            background_color: color.to_string(),
+            ..Style::default()
+        };
+
+        MoveGizmo {
+            position: Coordinate { x: 0., y: 0. },
+            affected_axis: None,
+            hovered_axis: None,
+            active: false,
+
+            snapping: None,
+            snap_hint: None,
+            view_scale: Cell::new(1.),
+
+            cursor_to_element_offset: Coordinate { x: 0., y: 0. },
+
+            offsets: HashMap::new(),
+            starts: HashMap::new(),
+            start_position: Coordinate { x: 0., y: 0. },
+
+            x_handle: GizmoArrow::new(Coordinate {
+                x: LINE_LENGTH,
+                y: 0.,
+            }),
+
+            y_handle: GizmoArrow::new(Coordinate {
+                x: 0.,
+                y: -LINE_LENGTH,
+            }),
+
+            style: InteractiveElementStyle {
+                normal: style("#C45D53"),
+                hover: style("#E8A09A"),
+                selected: style("#1E88E5"),
+            },
+        }
+    }
+
+    /// Returns the axis of the handle at `mouse_pos`. The handle at the origin takes precedence over the arrows,
+    /// which start there as well.
+    pub fn axis_at(&self, mouse_pos: Coordinate<f64>) -> Option<Axis> {
+        let diff = (mouse_pos - self.position) * self.view_scale.get();
+        let reach = CENTER_SIZE / 2. + HANDLE_TOLERANCE;
+
+        if diff.x.abs() <= reach && diff.y.abs() <= reach {
+            return Some(Axis::XY);
+        }
+
+        if self.x_handle.hit(diff) {
+            return Some(Axis::X);
+        }
+
+        if self.y_handle.hit(diff) {
+            return Some(Axis::Y);
+        }
+
+        None
+    }
+
+    pub fn mouse_over(&self, mouse_pos: Coordinate<f64>) -> bool {
+        self.axis_at(mouse_pos).is_some()
+    }
+
+    /// Returns the axis of the handle below the cursor, updated whenever the mouse moves without dragging.
+    pub fn hovered_axis(&self) -> Option<Axis> {
+        self.hovered_axis
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Snaps the position of the gizmo while it is dragged, see [App::snapping](crate::ui::app::App::snapping).
+    pub fn set_snapping(&mut self, snapping: Rc<RefCell<Snapping>>) {
+        self.snapping = Some(snapping);
+    }
+
+    fn handle_style(&self, axis: Axis) -> &Style {
+        if self.active && self.affected_axis == Some(axis) {
+            &self.style.selected
+        } else if !self.active && self.hovered_axis == Some(axis) {
+            &self.style.hover
+        } else {
+            &self.style.normal
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_element {
+    use geo::{Coordinate, Rect};
+    use uuid::Uuid;
+
+    use super::{ElementsById, GetPosition, GetRotation, Id, Scale, SetPosition, SetRotation};
+
+    /// Element that can be moved, rotated and scaled by every gizmo. Its position is the center of its bounds.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Element {
+        pub id: Uuid,
+        pub position: Coordinate<f64>,
+        pub rotation: f64,
+        pub size: Coordinate<f64>,
+    }
+
+    impl Element {
+        pub fn at(position: Coordinate<f64>) -> Self {
+            Element {
+                id: Uuid::new_v4(),
+                position,
+                rotation: 0.,
+                size: Coordinate { x: 0., y: 0. },
+            }
+        }
+
+        pub fn with_rect(rect: Rect<f64>) -> Self {
+            Element {
+                size: Coordinate {
+                    x: rect.width(),
+                    y: rect.height(),
+                },
+                ..Element::at(rect.center())
+            }
+        }
+
+        pub fn rect(&self) -> Rect<f64> {
+            Rect::new(
+                self.position - self.size / 2.,
+                self.position + self.size / 2.,
+            )
+        }
+    }
+
+    impl GetPosition for Element {
+        fn position(&self) -> Coordinate<f64> {
+            self.position
+        }
+    }
+
+    impl SetPosition for Element {
+        fn set_position(&mut self, position: Coordinate<f64>) {
+            self.position = position;
+        }
+    }
+
+    impl GetRotation for Element {
+        fn rotation(&self) -> f64 {
+            self.rotation
+        }
+    }
+
+    impl SetRotation for Element {
+        fn set_rotation(&mut self, rotation: f64) {
+            self.rotation = rotation;
+        }
+    }
+
+    impl Id for Element {
+        fn id(&self) -> Uuid {
+            self.id
+        }
+    }
+
+    impl Scale for Element {
+        fn bounding_rect(&self) -> Rect<f64> {
+            self.rect()
+        }
+
+        fn scale(&mut self, factor: Coordinate<f64>, origin: Coordinate<f64>) {
+            self.position = Coordinate {
+                x: origin.x + (self.position.x - origin.x) * factor.x,
+                y: origin.y + (self.position.y - origin.y) * factor.y,
+            };
+            self.size = Coordinate {
+                x: self.size.x * factor.x.abs(),
+                y: self.size.y * factor.y.abs(),
+            };
+        }
+    }
+
+    impl ElementsById<Element> for Vec<Element> {
+        fn element_by_id_mut(&mut self, id: &Uuid) -> Option<&mut Element> {
+            self.iter_mut().find(|x| x.id == *id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::Coordinate;
+
+    use super::{test_element::Element, Axis, GetPosition, Gizmo, MoveGizmo, SetPosition};
+
+    #[test]
+    fn handles_are_hit_along_their_axis() {
+        let mut gizmo = MoveGizmo::new();
+        gizmo.set_position(Coordinate { x: 100., y: 100. });
+
+        let axis_at = |x: f64, y: f64| gizmo.axis_at(Coordinate { x, y });
+        assert_eq!(axis_at(100., 100.), Some(Axis::XY));
+        assert_eq!(axis_at(150., 103.), Some(Axis::X));
+        assert_eq!(axis_at(200., 100.), Some(Axis::X));
+        assert_eq!(axis_at(97., 50.), Some(Axis::Y));
+        assert_eq!(axis_at(150., 50.), None);
+        assert_eq!(axis_at(50., 100.), None);
+        assert_eq!(axis_at(100., 150.), None);
+
+        let mut elements = vec![Element::at(Coordinate { x: 90., y: 110. })];
+
+        // Hovering does not start a drag
+        Gizmo::<Element>::mouse_move(
+            &mut gizmo,
+            Coordinate { x: 150., y: 100. },
+            elements.iter_mut(),
+        );
+        assert_eq!(gizmo.hovered_axis(), Some(Axis::X));
+        assert_eq!(elements[0].position, Coordinate { x: 90., y: 110. });
+
+        gizmo.mouse_down(Coordinate { x: 150., y: 100. }, 0, elements.iter_mut());
+        assert_eq!(gizmo.affected_axis, Some(Axis::X));
+        Gizmo::<Element>::mouse_move(
+            &mut gizmo,
+            Coordinate { x: 170., y: 140. },
+            elements.iter_mut(),
+        );
+        assert_eq!(elements[0].position, Coordinate { x: 110., y: 110. });
+        assert_eq!(gizmo.position(), Coordinate { x: 120., y: 100. });
+
+        // Handles keep their size on screen, so they cover less of the world when zoomed in
+        gizmo.view_scale.set(2.);
+        assert_eq!(
+            gizmo.axis_at(Coordinate { x: 160., y: 100. }),
+            Some(Axis::X)
+        );
+        assert_eq!(gizmo.axis_at(Coordinate { x: 200., y: 100. }), None);
+        gizmo.view_scale.set(1.);
+
+        // Missing every handle leaves no axis behind
+        gizmo.mouse_up(Coordinate { x: 170., y: 140. }, 0, elements.iter_mut());
+        gizmo.mouse_down(Coordinate { x: 0., y: 0. }, 0, elements.iter_mut());
+        assert_eq!(gizmo.affected_axis, None);
+        assert!(!gizmo.is_active());
+    }
+}
@@ -0,0 +1,199 @@
+use geo::Coordinate;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use super::{draw_in_screen_space, Axis};
+use crate::input::keyboard::Key;
+
+/// Offset of the overlay from the position it belongs to in screen pixels
+static OVERLAY_OFFSET: f64 = 16.0;
+
+static OVERLAY_HEIGHT: f64 = 20.0;
+
+/// A number typed during a drag, optionally restricted to an axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumericValue {
+    pub axis: Option<Axis>,
+    pub value: f64,
+}
+
+/// What a key pressed during a drag means for the [NumericInput].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumericEvent {
+    /// The typed value changed and should be applied with [Gizmo::set_value](super::Gizmo::set_value)
+    Changed,
+
+    /// Enter was pressed, the drag should be ended with [Gizmo::mouse_up](super::Gizmo::mouse_up)
+    Commit,
+
+    /// Escape was pressed, the drag should be aborted with [Gizmo::cancel](super::Gizmo::cancel)
+    Cancel,
+
+    /// The key is not part of a number
+    Ignored,
+}
+
+/// Collects the keys typed while a gizmo is dragged into a number, so that elements can be moved, rotated or scaled by
+/// an exact value.
+///
+/// A value may start with an axis letter, e.g. `x 25` moves by 25 along the x axis only. Without a letter the gizmo
+/// decides which axes the value applies to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NumericInput {
+    axis: Option<Axis>,
+    text: String,
+}
+
+impl NumericInput {
+    pub fn key_down(&mut self, key: Key) -> NumericEvent {
+        let character = match key {
+            Key::Return | Key::NumPadEnter if !self.is_empty() => return NumericEvent::Commit,
+            Key::Escape => return NumericEvent::Cancel,
+            Key::Backspace => {
+                if self.text.pop().is_none() {
+                    self.axis = None;
+                }
+
+                return NumericEvent::Changed;
+            }
+            Key::X | Key::Y if self.is_empty() => {
+                self.axis = Some(match key {
+                    Key::X => Axis::X,
+                    _ => Axis::Y,
+                });
+
+                return NumericEvent::Changed;
+            }
+            Key::Minus | Key::NumPadMinus if self.text.is_empty() => '-',
+            Key::Period | Key::Comma | Key::NumPadPeriod if !self.text.contains('.') => '.',
+            _ => match digit(key) {
+                Some(digit) => digit,
+                None => return NumericEvent::Ignored,
+            },
+        };
+
+        self.text.push(character);
+        NumericEvent::Changed
+    }
+
+    /// Returns the typed value, `None` while it is not a number yet, e.g. just a minus.
+    pub fn value(&self) -> Option<NumericValue> {
+        let value = self.text.parse::<f64>().ok()?;
+
+        Some(NumericValue {
+            axis: self.axis,
+            value,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axis.is_none() && self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.axis = None;
+        self.text.clear();
+    }
+
+    /// Returns the typed text as shown to the user, e.g. `x: -12.5`.
+    pub fn label(&self) -> String {
+        match self.axis {
+            Some(Axis::X) => format!("x: {}", self.text),
+            Some(Axis::Y) => format!("y: {}", self.text),
+            Some(Axis::XY) | None => self.text.clone(),
+        }
+    }
+
+    /// Draws the typed text in a small box next to `position`, which is in world coordinates. `unit` is appended to
+    /// the text, e.g. `°` while rotating. Nothing is drawn while no key was typed.
+    pub fn render(
+        &self,
+        context: &CanvasRenderingContext2d,
+        position: Coordinate<f64>,
+        unit: &str,
+    ) -> Result<(), JsValue> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let label = format!("{}{}", self.label(), unit);
+
+        draw_in_screen_space(context, position, || {
+            let width = context.measure_text(&label)?.width() + 12.;
+
+            context.set_fill_style_str("#2A2A2B");
+            context.fill_rect(OVERLAY_OFFSET, OVERLAY_OFFSET, width, OVERLAY_HEIGHT);
+            context.set_line_width(1.);
+            context.set_stroke_style_str("#1E88E5");
+            context.stroke_rect(OVERLAY_OFFSET, OVERLAY_OFFSET, width, OVERLAY_HEIGHT);
+
+            context.set_fill_style_str("#FFFFFF");
+            context.set_text_baseline("middle");
+            context.fill_text(
+                &label,
+                OVERLAY_OFFSET + 6.,
+                OVERLAY_OFFSET + OVERLAY_HEIGHT / 2.,
+            )
+        })
+    }
+}
+
+fn digit(key: Key) -> Option<char> {
+    let digit = match key {
+        Key::D0 | Key::NumPad0 => 0,
+        Key::D1 | Key::NumPad1 => 1,
+        Key::D2 | Key::NumPad2 => 2,
+        Key::D3 | Key::NumPad3 => 3,
+        Key::D4 | Key::NumPad4 => 4,
+        Key::D5 | Key::NumPad5 => 5,
+        Key::D6 | Key::NumPad6 => 6,
+        Key::D7 | Key::NumPad7 => 7,
+        Key::D8 | Key::NumPad8 => 8,
+        Key::D9 | Key::NumPad9 => 9,
+        _ => return None,
+    };
+
+    char::from_digit(digit, 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::Coordinate;
+
+    use super::{NumericEvent, NumericInput};
+    use crate::{
+        gizmo::{test_element::Element, Axis, GetPosition, Gizmo, MoveGizmo, SetPosition},
+        input::keyboard::Key,
+    };
+
+    #[test]
+    fn typed_values_move_elements() {
+        let mut input = NumericInput::default();
+        for key in [Key::Y, Key::Minus, Key::D1, Key::D2, Key::Period, Key::D5] {
+            assert_eq!(input.key_down(key), NumericEvent::Changed);
+        }
+        assert_eq!(input.key_down(Key::X), NumericEvent::Ignored);
+        assert_eq!(input.label(), "y: -12.5");
+
+        let value = input.value().unwrap();
+        assert_eq!(value.axis, Some(Axis::Y));
+        // Browsers name the key "Enter"
+        let enter = Key::from(String::from("Enter"));
+        assert_eq!(input.key_down(enter), NumericEvent::Commit);
+
+        let start = Coordinate { x: 10., y: 20. };
+        let mut elements = vec![Element::at(start)];
+
+        // Dragging the x arrow, but the typed axis wins
+        let mut gizmo = MoveGizmo::new();
+        gizmo.set_position(start);
+        gizmo.mouse_down(Coordinate { x: 60., y: 20. }, 0, elements.iter_mut());
+        Gizmo::<Element>::set_value(&mut gizmo, value, elements.iter_mut());
+        assert_eq!(elements[0].position, Coordinate { x: 10., y: 7.5 });
+
+        Gizmo::<Element>::cancel(&mut gizmo, elements.iter_mut());
+        assert_eq!(elements[0].position, start);
+        assert_eq!(gizmo.position(), start);
+        assert!(!gizmo.is_active());
+    }
+}
@@ -0,0 +1,342 @@
+use std::{cell::Cell, collections::HashMap, f64::consts::PI};
+
+use geo::{prelude::EuclideanDistance, Coordinate};
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+
+use super::{
+    draw_in_screen_space, view_scale, GetPosition, GetRotation, Gizmo, Id, Modifiers, NumericValue,
+    RotateAction, SetPosition, SetRotation,
+};
+
+/// Radius of the ring handle in screen pixels
+static RING_RADIUS: f64 = 60.0;
+
+/// Distance from the ring within which the ring can be grabbed
+static RING_TOLERANCE: f64 = 6.0;
+
+/// Angles are rounded to multiples of this while Shift is pressed
+static SNAP_ANGLE: f64 = PI / 12.;
+
+static RING_COLOR: &str = "#5387C4";
+static ACTIVE_COLOR: &str = "#1E88E5";
+
+/// Rotates elements around a pivot by dragging a ring around it.
+///
+/// The position of the gizmo is the pivot. Elements keep their position relative to the pivot, so rotating a selection
+/// of several elements rotates the selection as a whole. Pressing Shift snaps the angle to steps of 15 degrees.
+pub struct RotateGizmo {
+    position: Coordinate<f64>,
+
+    /// Angle of the cursor relative to the pivot when the drag started
+    start_angle: f64,
+
+    /// Angle of the cursor relative to the pivot at the previous mouse move
+    cursor_angle: f64,
+
+    /// Rotation of the cursor around the pivot since the drag started. Summed up move by move, so that it is not limited
+    /// to half a turn in each direction.
+    turned: f64,
+
+    /// Rotation applied since the drag started, in radians
+    angle: f64,
+
+    /// Position and rotation of each element when the drag started
+    starts: HashMap<Uuid, (Coordinate<f64>, f64)>,
+
+    active: bool,
+    snap: bool,
+
+    /// Zoom of the camera when the gizmo was rendered last, used to hit-test the ring at its size on screen
+    view_scale: Cell<f64>,
+}
+
+impl Default for RotateGizmo {
+    fn default() -> Self {
+        RotateGizmo::new()
+    }
+}
+
+impl RotateGizmo {
+    pub fn new() -> Self {
+        RotateGizmo {
+            position: Coordinate { x: 0., y: 0. },
+            start_angle: 0.,
+            cursor_angle: 0.,
+            turned: 0.,
+            angle: 0.,
+            starts: HashMap::new(),
+            active: false,
+            snap: false,
+            view_scale: Cell::new(1.),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the rotation applied by the current drag in radians.
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Returns true if `mouse_pos` is on the ring handle.
+    pub fn mouse_over(&self, mouse_pos: Coordinate<f64>) -> bool {
+        let distance = mouse_pos.euclidean_distance(&self.position) * self.view_scale.get();
+        (distance - RING_RADIUS).abs() <= RING_TOLERANCE
+    }
+
+    /// Rotates the elements by `angle` radians relative to their state when the drag started.
+    fn rotate<'a, T>(&mut self, angle: f64, elements: impl Iterator<Item = &'a mut T>)
+    where
+        T: SetPosition + SetRotation + Id + 'a,
+    {
+        self.angle = angle;
+
+        for x in elements {
+            if let Some((position, rotation)) = self.starts.get(&x.id()) {
+                x.set_position(rotate_around(*position, self.position, angle));
+                x.set_rotation(rotation + angle);
+            }
+        }
+    }
+
+    fn cursor_angle(&self, mouse_pos: Coordinate<f64>) -> f64 {
+        let diff = mouse_pos - self.position;
+        diff.y.atan2(diff.x)
+    }
+}
+
+/// Rotates `point` by `angle` radians around `pivot`.
+pub fn rotate_around(
+    point: Coordinate<f64>,
+    pivot: Coordinate<f64>,
+    angle: f64,
+) -> Coordinate<f64> {
+    let (sin, cos) = angle.sin_cos();
+    let diff = point - pivot;
+
+    Coordinate {
+        x: pivot.x + diff.x * cos - diff.y * sin,
+        y: pivot.y + diff.x * sin + diff.y * cos,
+    }
+}
+
+/// Returns `angle` normalized to the range (-PI, PI].
+fn normalize(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(2. * PI);
+    match angle > PI {
+        true => angle - 2. * PI,
+        false => angle,
+    }
+}
+
+impl GetPosition for RotateGizmo {
+    fn position(&self) -> Coordinate<f64> {
+        self.position
+    }
+}
+
+impl SetPosition for RotateGizmo {
+    fn set_position(&mut self, position: Coordinate<f64>) {
+        self.position = position;
+    }
+}
+
+impl<'a, T> Gizmo<'a, T> for RotateGizmo
+where
+    T: GetPosition + SetPosition + GetRotation + SetRotation + Id + 'a,
+{
+    type Action = RotateAction<T>;
+
+    fn mouse_down(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        _button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        self.active = self.mouse_over(mouse_pos);
+        if !self.active {
+            return;
+        }
+
+        self.start_angle = self.cursor_angle(mouse_pos);
+        self.cursor_angle = self.start_angle;
+        self.turned = 0.;
+        self.angle = 0.;
+        self.starts = elements
+            .map(|x| (x.id(), (x.position(), x.rotation())))
+            .collect();
+    }
+
+    fn mouse_move(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        if !self.active {
+            return;
+        }
+
+        // The cursor moves only a little between two moves, so the shortest way between its angles is the one it took
+        let cursor_angle = self.cursor_angle(mouse_pos);
+        self.turned += normalize(cursor_angle - self.cursor_angle);
+        self.cursor_angle = cursor_angle;
+
+        let mut angle = self.turned;
+        if self.snap {
+            angle = (angle / SNAP_ANGLE).round() * SNAP_ANGLE;
+        }
+        self.rotate(angle, elements);
+    }
+
+    fn mouse_up(
+        &mut self,
+        _mouse_pos: Coordinate<f64>,
+        _button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) -> Option<RotateAction<T>> {
+        let was_active = self.active;
+        let angle = self.angle;
+        let starts = std::mem::take(&mut self.starts);
+
+        self.active = false;
+        self.angle = 0.;
+
+        if !was_active || angle == 0. {
+            return None;
+        }
+
+        let states = elements
+            .filter_map(|x| {
+                let start = *starts.get(&x.id())?;
+                Some((x.id(), start, (x.position(), x.rotation())))
+            })
+            .collect();
+
+        Some(RotateAction::new(states))
+    }
+
+    fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.snap = modifiers.shift;
+    }
+
+    /// Rotates the elements by the typed value in degrees.
+    fn set_value(&mut self, value: NumericValue, elements: impl Iterator<Item = &'a mut T>) {
+        if self.active {
+            self.rotate(value.value.to_radians(), elements);
+        }
+    }
+
+    fn cancel(&mut self, elements: impl Iterator<Item = &'a mut T>) {
+        if !self.active {
+            return;
+        }
+
+        self.rotate(0., elements);
+        self.active = false;
+        self.starts.clear();
+    }
+
+    fn render(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Result<(), JsValue> {
+        if elements.peekable().peek().is_none() {
+            return Ok(());
+        }
+
+        self.view_scale.set(view_scale(context));
+
+        let color = match self.active {
+            true => ACTIVE_COLOR,
+            false => RING_COLOR,
+        };
+
+        draw_in_screen_space(context, self.position, || {
+            context.begin_path();
+            context.arc(0., 0., RING_RADIUS, 0., 2. * PI)?;
+            context.set_line_width(2.);
+            context.set_stroke_style_str(color);
+            context.stroke();
+
+            if !self.active {
+                return Ok(());
+            }
+
+            // The swept angle and a readout of it in degrees
+            context.begin_path();
+            context.move_to(0., 0.);
+            context.arc_with_anticlockwise(
+                0.,
+                0.,
+                RING_RADIUS,
+                self.start_angle,
+                self.start_angle + self.angle,
+                self.angle < 0.,
+            )?;
+            context.close_path();
+            context.set_global_alpha(0.3);
+            context.set_fill_style_str(color);
+            context.fill();
+            context.set_global_alpha(1.);
+
+            context.fill_text(
+                &format!("{:.1}°", self.angle.to_degrees()),
+                RING_RADIUS + RING_TOLERANCE * 2.,
+                -RING_RADIUS,
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use geo::Coordinate;
+
+    use super::RotateGizmo;
+    use crate::gizmo::{test_element::Element, Gizmo, Modifiers};
+
+    #[test]
+    fn elements_rotate_around_pivot() {
+        let mut elements = vec![Element::at(Coordinate { x: 10., y: 0. })];
+
+        let mut gizmo = RotateGizmo::new();
+        gizmo.mouse_down(Coordinate { x: 60., y: 0. }, 0, elements.iter_mut());
+        assert!(gizmo.is_active());
+
+        // A quarter turn, slightly off so that snapping has something to do
+        Gizmo::<Element>::set_modifiers(
+            &mut gizmo,
+            Modifiers {
+                shift: true,
+                ..Modifiers::default()
+            },
+        );
+        Gizmo::<Element>::mouse_move(
+            &mut gizmo,
+            Coordinate { x: 2., y: 60. },
+            elements.iter_mut(),
+        );
+
+        assert!((gizmo.angle() - PI / 2.).abs() < 1e-9);
+        assert!((elements[0].rotation - PI / 2.).abs() < 1e-9);
+        assert!((elements[0].position.x - 0.).abs() < 1e-9);
+        assert!((elements[0].position.y - 10.).abs() < 1e-9);
+
+        // Turning further than half a turn keeps counting instead of jumping to the opposite direction
+        for position in [
+            Coordinate { x: -60., y: 2. },
+            Coordinate { x: -2., y: -60. },
+        ] {
+            Gizmo::<Element>::mouse_move(&mut gizmo, position, elements.iter_mut());
+        }
+        assert!((gizmo.angle() - 3. * PI / 2.).abs() < 1e-9);
+    }
+}
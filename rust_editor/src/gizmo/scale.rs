@@ -0,0 +1,575 @@
+use std::cell::Cell;
+
+use geo::{Coordinate, Rect};
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+
+use super::{
+    draw_in_screen_space, view_scale, Axis, GetPosition, Gizmo, Id, Modifiers, NumericValue,
+    ScaleAction, SetPosition,
+};
+use crate::{renderer::PrimitiveRenderer, style::Style};
+
+/// Width and height of a handle in screen pixels
+static HANDLE_SIZE: f64 = 8.0;
+
+/// Distance from a handle within which the handle can still be grabbed
+static HANDLE_TOLERANCE: f64 = 4.0;
+
+/// Elements are never scaled below this factor, which keeps them from collapsing into a point
+static MIN_SCALE: f64 = 0.01;
+
+static FRAME_COLOR: &str = "#5387C4";
+static ACTIVE_COLOR: &str = "#1E88E5";
+
+/// Elements that can be resized with the [ScaleGizmo].
+pub trait Scale {
+    /// Axis aligned rectangle that encloses the element
+    fn bounding_rect(&self) -> Rect<f64>;
+
+    /// Scales the element by `factor` along each axis, keeping `origin` in place.
+    fn scale(&mut self, factor: Coordinate<f64>, origin: Coordinate<f64>);
+}
+
+/// One of the eight handles on the corners and edges of the bounding rect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Handle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+impl Handle {
+    const ALL: [Handle; 8] = [
+        Handle::TopLeft,
+        Handle::Top,
+        Handle::TopRight,
+        Handle::Right,
+        Handle::BottomRight,
+        Handle::Bottom,
+        Handle::BottomLeft,
+        Handle::Left,
+    ];
+
+    /// Side of the rect the handle is on per axis, -1 for left or top, 0 for the middle and 1 for right or bottom
+    fn side(&self) -> Coordinate<f64> {
+        let (x, y) = match self {
+            Handle::TopLeft => (-1., -1.),
+            Handle::Top => (0., -1.),
+            Handle::TopRight => (1., -1.),
+            Handle::Right => (1., 0.),
+            Handle::BottomRight => (1., 1.),
+            Handle::Bottom => (0., 1.),
+            Handle::BottomLeft => (-1., 1.),
+            Handle::Left => (-1., 0.),
+        };
+
+        Coordinate { x, y }
+    }
+
+    fn position(&self, rect: &Rect<f64>) -> Coordinate<f64> {
+        let side = self.side();
+        let center = rect.center();
+
+        Coordinate {
+            x: center.x + side.x * rect.width() / 2.,
+            y: center.y + side.y * rect.height() / 2.,
+        }
+    }
+
+    fn is_corner(&self) -> bool {
+        let side = self.side();
+        side.x != 0. && side.y != 0.
+    }
+}
+
+/// Resizes elements by dragging the handles on the corners and edges of their bounding rect.
+///
+/// Dragging a handle keeps the opposite side in place. Pressing Shift keeps the aspect ratio and pressing Alt scales
+/// around the center of the bounding rect instead.
+pub struct ScaleGizmo {
+    /// Bounding rect of the elements when the drag started
+    rect: Rect<f64>,
+
+    /// Offset from the grabbed handle to the cursor when the drag started
+    grab_offset: Coordinate<f64>,
+
+    /// Scale applied to the elements since the drag started
+    factor: Coordinate<f64>,
+
+    /// Point that stays in place during the current drag
+    origin: Coordinate<f64>,
+
+    /// Elements that are scaled by the current drag
+    ids: Vec<Uuid>,
+
+    handle: Option<Handle>,
+    modifiers: Modifiers,
+
+    /// Zoom of the camera when the gizmo was rendered last, used to hit-test the handles at their size on screen
+    view_scale: Cell<f64>,
+}
+
+impl Default for ScaleGizmo {
+    fn default() -> Self {
+        ScaleGizmo::new()
+    }
+}
+
+impl ScaleGizmo {
+    pub fn new() -> Self {
+        ScaleGizmo {
+            rect: Rect::new(Coordinate { x: 0., y: 0. }, Coordinate { x: 0., y: 0. }),
+            grab_offset: Coordinate { x: 0., y: 0. },
+            factor: Coordinate { x: 1., y: 1. },
+            origin: Coordinate { x: 0., y: 0. },
+            ids: Vec::new(),
+            handle: None,
+            modifiers: Modifiers::default(),
+            view_scale: Cell::new(1.),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Returns the scale applied by the current drag along each axis.
+    pub fn factor(&self) -> Coordinate<f64> {
+        self.factor
+    }
+
+    /// Returns true if `mouse_pos` is on one of the handles of `rect`.
+    pub fn mouse_over(&self, mouse_pos: Coordinate<f64>, rect: &Rect<f64>) -> bool {
+        handle_at(mouse_pos, rect, self.view_scale.get()).is_some()
+    }
+
+    /// Returns the point that stays in place while `handle` is dragged, the opposite side or, with Alt, the center.
+    fn origin(&self, handle: Handle) -> Coordinate<f64> {
+        let side = handle.side();
+        let start = handle.position(&self.rect);
+
+        match self.modifiers.alt {
+            true => self.rect.center(),
+            false => Coordinate {
+                x: start.x - side.x * self.rect.width(),
+                y: start.y - side.y * self.rect.height(),
+            },
+        }
+    }
+
+    /// Scales the elements so that their total scale since the drag started is `factor`.
+    fn apply<'a, T: Scale + 'a>(
+        &mut self,
+        factor: Coordinate<f64>,
+        origin: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        // Elements were already scaled by the previous move, so only the difference is applied. If the origin changed,
+        // e.g. because Alt was pressed, the previous scale is reverted around the old origin first, otherwise the
+        // elements would end up shifted relative to a scale by `factor` around `origin`.
+        let inverse = Coordinate {
+            x: 1. / self.factor.x,
+            y: 1. / self.factor.y,
+        };
+        let step = Coordinate {
+            x: factor.x / self.factor.x,
+            y: factor.y / self.factor.y,
+        };
+        for x in elements {
+            match origin == self.origin {
+                true => x.scale(step, origin),
+                false => {
+                    x.scale(inverse, self.origin);
+                    x.scale(factor, origin);
+                }
+            }
+        }
+
+        self.factor = factor;
+        self.origin = origin;
+    }
+
+    /// Computes the scale along each axis and the point that stays in place for the handle dragged to `mouse_pos`.
+    fn transform(
+        &self,
+        handle: Handle,
+        mouse_pos: Coordinate<f64>,
+    ) -> (Coordinate<f64>, Coordinate<f64>) {
+        let side = handle.side();
+        let start = handle.position(&self.rect);
+        let origin = self.origin(handle);
+
+        let target = mouse_pos - self.grab_offset;
+        let axis_factor = |side: f64, start: f64, target: f64, origin: f64| {
+            let length = start - origin;
+            match side == 0. || length == 0. {
+                true => 1.,
+                false => ((target - origin) / length).max(MIN_SCALE),
+            }
+        };
+
+        let mut factor = Coordinate {
+            x: axis_factor(side.x, start.x, target.x, origin.x),
+            y: axis_factor(side.y, start.y, target.y, origin.y),
+        };
+
+        if self.modifiers.shift {
+            let uniform = match handle.is_corner() {
+                true => factor.x.max(factor.y),
+                false if side.x != 0. => factor.x,
+                false => factor.y,
+            };
+            factor = Coordinate {
+                x: uniform,
+                y: uniform,
+            };
+        }
+
+        (factor, origin)
+    }
+}
+
+fn handle_at(mouse_pos: Coordinate<f64>, rect: &Rect<f64>, view_scale: f64) -> Option<Handle> {
+    let reach = HANDLE_SIZE / 2. + HANDLE_TOLERANCE;
+
+    Handle::ALL.into_iter().find(|handle| {
+        let diff = (mouse_pos - handle.position(rect)) * view_scale;
+        diff.x.abs() <= reach && diff.y.abs() <= reach
+    })
+}
+
+/// Returns the rect that encloses all `rects`.
+fn union(mut rects: impl Iterator<Item = Rect<f64>>) -> Option<Rect<f64>> {
+    let first = rects.next()?;
+
+    Some(rects.fold(first, |acc, rect| {
+        Rect::new(
+            Coordinate {
+                x: acc.min().x.min(rect.min().x),
+                y: acc.min().y.min(rect.min().y),
+            },
+            Coordinate {
+                x: acc.max().x.max(rect.max().x),
+                y: acc.max().y.max(rect.max().y),
+            },
+        )
+    }))
+}
+
+impl GetPosition for ScaleGizmo {
+    fn position(&self) -> Coordinate<f64> {
+        self.rect.center()
+    }
+}
+
+impl SetPosition for ScaleGizmo {
+    fn set_position(&mut self, position: Coordinate<f64>) {
+        let offset = position - self.rect.center();
+        self.rect = Rect::new(self.rect.min() + offset, self.rect.max() + offset);
+    }
+}
+
+impl<'a, T> Gizmo<'a, T> for ScaleGizmo
+where
+    T: GetPosition + SetPosition + Scale + Id + 'a,
+{
+    type Action = ScaleAction<T>;
+
+    fn mouse_down(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        _button: u32,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        self.handle = None;
+        self.factor = Coordinate { x: 1., y: 1. };
+
+        let (ids, rects): (Vec<Uuid>, Vec<Rect<f64>>) =
+            elements.map(|x| (x.id(), x.bounding_rect())).unzip();
+        if let Some(rect) = union(rects.into_iter()) {
+            self.rect = rect;
+            self.handle = handle_at(mouse_pos, &rect, self.view_scale.get());
+        }
+        self.ids = ids;
+
+        if let Some(handle) = self.handle {
+            self.grab_offset = mouse_pos - handle.position(&self.rect);
+            self.origin = self.origin(handle);
+        }
+    }
+
+    fn mouse_move(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) {
+        let handle = match self.handle {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let (factor, origin) = self.transform(handle, mouse_pos);
+        self.apply(factor, origin, elements);
+    }
+
+    fn mouse_up(
+        &mut self,
+        _mouse_pos: Coordinate<f64>,
+        _button: u32,
+        _elements: impl Iterator<Item = &'a mut T>,
+    ) -> Option<ScaleAction<T>> {
+        let was_active = self.handle.is_some();
+        let factor = self.factor;
+        let ids = std::mem::take(&mut self.ids);
+
+        self.handle = None;
+        self.factor = Coordinate { x: 1., y: 1. };
+
+        match was_active && factor != (Coordinate { x: 1., y: 1. }) {
+            true => Some(ScaleAction::new(ids, factor, self.origin)),
+            false => None,
+        }
+    }
+
+    fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    /// Scales the elements by the typed factor. Without an axis letter, corner handles scale both axes and edge
+    /// handles the axis they move along.
+    fn set_value(&mut self, value: NumericValue, elements: impl Iterator<Item = &'a mut T>) {
+        let handle = match self.handle {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let axis = value.axis;
+        let value = value.value.max(MIN_SCALE);
+        let side = handle.side();
+        let factor = match axis {
+            Some(Axis::X) => Coordinate { x: value, y: 1. },
+            Some(Axis::Y) => Coordinate { x: 1., y: value },
+            _ if handle.is_corner() => Coordinate { x: value, y: value },
+            _ => Coordinate {
+                x: if side.x != 0. { value } else { 1. },
+                y: if side.y != 0. { value } else { 1. },
+            },
+        };
+
+        let origin = self.origin(handle);
+        self.apply(factor, origin, elements);
+    }
+
+    fn cancel(&mut self, elements: impl Iterator<Item = &'a mut T>) {
+        if self.handle.is_none() {
+            return;
+        }
+
+        self.apply(Coordinate { x: 1., y: 1. }, self.origin, elements);
+        self.handle = None;
+        self.ids.clear();
+    }
+
+    fn render(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Result<(), JsValue> {
+        let rect = match union(elements.map(|x| x.bounding_rect())) {
+            Some(rect) => rect,
+            None => return Ok(()),
+        };
+
+        let scale = view_scale(context);
+        self.view_scale.set(scale);
+
+        // The frame and the handles are drawn relative to the top left corner, in screen pixels
+        let to_screen = |position: Coordinate<f64>| (position - rect.min()) * scale;
+
+        draw_in_screen_space(context, rect.min(), || {
+            context.begin_path();
+            Rect::new(to_screen(rect.min()), to_screen(rect.max())).render(
+                &Style {
+                    border_width: 1,
+                    border_color: FRAME_COLOR.to_string(),
+                    background_color: "transparent".to_string(),
+                    ..Style::default()
+                },
+                context,
+            )?;
+
+            let half = Coordinate {
+                x: HANDLE_SIZE / 2.,
+                y: HANDLE_SIZE / 2.,
+            };
+            for handle in Handle::ALL {
+                let position = to_screen(handle.position(&rect));
+                let background_color = match self.handle == Some(handle) {
+                    true => ACTIVE_COLOR,
+                    false => "#FFFFFF",
+                };
+
+                context.begin_path();
+                Rect::new(position - half, position + half).render(
+                    &Style {
+                        border_width: 1,
+                        border_color: FRAME_COLOR.to_string(),
+                        background_color: background_color.to_string(),
+                        ..Style::default()
+                    },
+                    context,
+                )?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Coordinate, Rect};
+
+    use super::ScaleGizmo;
+    use crate::actions::Undo;
+    use crate::gizmo::{test_element::Element, Gizmo, Modifiers};
+
+    fn element() -> Element {
+        Element::with_rect(Rect::new(
+            Coordinate { x: 0., y: 0. },
+            Coordinate { x: 100., y: 50. },
+        ))
+    }
+
+    fn drag(modifiers: Modifiers, from: Coordinate<f64>, to: Coordinate<f64>) -> Rect<f64> {
+        let mut elements = vec![element()];
+        let mut gizmo = ScaleGizmo::new();
+
+        Gizmo::<Element>::set_modifiers(&mut gizmo, modifiers);
+        gizmo.mouse_down(from, 0, elements.iter_mut());
+        assert!(gizmo.is_active());
+
+        // Several moves must add up to the same result as a single one
+        let halfway = Coordinate {
+            x: (from.x + to.x) / 2.,
+            y: (from.y + to.y) / 2.,
+        };
+        Gizmo::<Element>::mouse_move(&mut gizmo, halfway, elements.iter_mut());
+        Gizmo::<Element>::mouse_move(&mut gizmo, to, elements.iter_mut());
+
+        elements[0].rect()
+    }
+
+    #[test]
+    fn handles_scale_elements() {
+        let bottom_right = Coordinate { x: 100., y: 50. };
+
+        // The opposite corner stays in place
+        let rect = drag(
+            Modifiers::default(),
+            bottom_right,
+            Coordinate { x: 200., y: 75. },
+        );
+        assert_eq!(
+            rect,
+            Rect::new(Coordinate { x: 0., y: 0. }, Coordinate { x: 200., y: 75. })
+        );
+
+        // An edge handle only scales one axis
+        let rect = drag(
+            Modifiers::default(),
+            Coordinate { x: 100., y: 25. },
+            Coordinate { x: 50., y: 80. },
+        );
+        assert_eq!(
+            rect,
+            Rect::new(Coordinate { x: 0., y: 0. }, Coordinate { x: 50., y: 50. })
+        );
+
+        // Shift keeps the aspect ratio
+        let uniform = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        let rect = drag(uniform, bottom_right, Coordinate { x: 200., y: 60. });
+        assert_eq!(
+            rect,
+            Rect::new(Coordinate { x: 0., y: 0. }, Coordinate { x: 200., y: 100. })
+        );
+
+        // Alt scales around the center
+        let centered = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+        let rect = drag(centered, bottom_right, Coordinate { x: 150., y: 75. });
+        assert_eq!(
+            rect,
+            Rect::new(
+                Coordinate { x: -50., y: -25. },
+                Coordinate { x: 150., y: 75. }
+            )
+        );
+    }
+
+    #[test]
+    fn origin_can_change_during_drag() {
+        let start = element().rect();
+        let centered = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+
+        let drag = |elements: &mut Vec<Element>| {
+            let mut gizmo = ScaleGizmo::new();
+            gizmo.mouse_down(Coordinate { x: 100., y: 50. }, 0, elements.iter_mut());
+            Gizmo::<Element>::mouse_move(
+                &mut gizmo,
+                Coordinate { x: 150., y: 75. },
+                elements.iter_mut(),
+            );
+            Gizmo::<Element>::set_modifiers(&mut gizmo, centered);
+            Gizmo::<Element>::mouse_move(
+                &mut gizmo,
+                Coordinate { x: 150., y: 75. },
+                elements.iter_mut(),
+            );
+
+            gizmo
+        };
+
+        // The result only depends on the modifiers pressed at the end
+        let mut elements = vec![element()];
+        let mut gizmo = drag(&mut elements);
+        assert_eq!(
+            elements[0].rect(),
+            Rect::new(
+                Coordinate { x: -50., y: -25. },
+                Coordinate { x: 150., y: 75. }
+            )
+        );
+
+        let mut action = Gizmo::<Element>::mouse_up(
+            &mut gizmo,
+            Coordinate { x: 150., y: 75. },
+            0,
+            elements.iter_mut(),
+        )
+        .unwrap();
+        action.undo(&mut elements);
+        assert_eq!(elements[0].rect(), start);
+
+        let mut elements = vec![element()];
+        let mut gizmo = drag(&mut elements);
+        Gizmo::<Element>::cancel(&mut gizmo, elements.iter_mut());
+        assert_eq!(elements[0].rect(), start);
+    }
+}
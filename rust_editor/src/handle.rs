@@ -0,0 +1,509 @@
+use std::{
+    cell::{Cell, RefCell},
+    f64::consts::PI,
+    fmt,
+    rc::Rc,
+};
+
+use geo::{prelude::EuclideanDistance, Coordinate};
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::{
+    actions::{Action, Redo, Undo},
+    gizmo::{draw_in_screen_space, view_scale, ElementsById, Id},
+    renderer::apply_style,
+    snapping::{SnapHint, Snapping},
+    style::{InteractiveElementStyle, Style},
+};
+
+/// Width and height of a handle in screen pixels
+static HANDLE_SIZE: f64 = 10.0;
+
+/// Distance from a handle in screen pixels within which the handle can still be grabbed
+static HANDLE_TOLERANCE: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandleShape {
+    Circle,
+    Square,
+    Diamond,
+}
+
+/// Moves `element` while the handle is dragged. Receives the position the handle was dragged to in world coordinates.
+pub type DragCallback<T> = Box<dyn Fn(&mut T, Coordinate<f64>)>;
+
+/// A point of an element that can be dragged to edit a property of the element, e.g. a corner radius or a control
+/// point of a curve.
+pub struct Handle<T> {
+    /// Shown in the history, e.g. `corner radius`
+    pub name: &'static str,
+
+    /// Position in world coordinates
+    pub position: Coordinate<f64>,
+
+    pub shape: HandleShape,
+
+    /// Css cursor shown while the handle is hovered or dragged, see https://developer.mozilla.org/en-US/docs/Web/CSS/cursor
+    pub cursor: &'static str,
+
+    pub on_drag: DragCallback<T>,
+}
+
+impl<T> Handle<T> {
+    /// Creates a round handle with the `move` cursor.
+    pub fn new(
+        name: &'static str,
+        position: Coordinate<f64>,
+        on_drag: impl Fn(&mut T, Coordinate<f64>) + 'static,
+    ) -> Self {
+        Handle {
+            name,
+            position,
+            shape: HandleShape::Circle,
+            cursor: "move",
+            on_drag: Box::new(on_drag),
+        }
+    }
+
+    pub fn with_shape(mut self, shape: HandleShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: &'static str) -> Self {
+        self.cursor = cursor;
+        self
+    }
+}
+
+/// Elements that have handles to edit their properties on the canvas.
+///
+/// Handles are identified by their index, so an element has to return its handles in the same order during a drag.
+pub trait Handles: Sized {
+    fn handles(&self) -> Vec<Handle<Self>>;
+}
+
+/// Identifies a handle as the element it belongs to and its index in [Handles::handles]
+type HandleId = (Uuid, usize);
+
+struct Drag<T> {
+    handle: HandleId,
+    name: &'static str,
+
+    /// Offset from the handle to the cursor when the drag started
+    grab_offset: Coordinate<f64>,
+
+    /// The element before the drag, used to undo it
+    before: T,
+}
+
+/// Hit-tests, renders and drags the [Handles] of elements.
+///
+/// Handles are drawn at a constant size on screen and styled with the normal, hover and selected style of an
+/// [InteractiveElementStyle]. Once a drag ends, [mouse_up](HandleEditor::mouse_up) returns a [HandleAction] with the
+/// element before and after the drag, which is added to the history with
+/// [App::finish_interaction](crate::ui::app::App::finish_interaction).
+pub struct HandleEditor<T> {
+    style: InteractiveElementStyle,
+    hovered: Option<HandleId>,
+    drag: Option<Drag<T>>,
+
+    snapping: Option<Rc<RefCell<Snapping>>>,
+    snap_hint: Option<SnapHint>,
+
+    /// Zoom of the camera when the handles were rendered last
+    view_scale: Cell<f64>,
+}
+
+impl<T> Default for HandleEditor<T>
+where
+    T: Handles + Id + Clone,
+{
+    fn default() -> Self {
+        HandleEditor::new()
+    }
+}
+
+impl<T> HandleEditor<T>
+where
+    T: Handles + Id + Clone,
+{
+    pub fn new() -> Self {
+        let style = |border_color: &str, background_color: &str| Style {
+            border_width: 1,
+            border_color: border_color.to_string(),
+            background_color: background_color.to_string(),
+            ..Style::default()
+        };
+
+        HandleEditor {
+            style: InteractiveElementStyle {
+                normal: style("#5387C4", "#FFFFFF"),
+                hover: style("#5387C4", "#BBDEFB"),
+                selected: style("#1E88E5", "#1E88E5"),
+            },
+            hovered: None,
+            drag: None,
+            snapping: None,
+            snap_hint: None,
+            view_scale: Cell::new(1.),
+        }
+    }
+
+    pub fn set_style(&mut self, style: InteractiveElementStyle) {
+        self.style = style;
+    }
+
+    /// Snaps dragged handles, see [App::snapping](crate::ui::app::App::snapping).
+    pub fn set_snapping(&mut self, snapping: Rc<RefCell<Snapping>>) {
+        self.snapping = Some(snapping);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Returns the css cursor of the dragged or hovered handle, `None` if the cursor is not on a handle.
+    pub fn cursor<'a>(&self, elements: impl Iterator<Item = &'a T>) -> Option<&'static str>
+    where
+        T: 'a,
+    {
+        let (id, index) = self
+            .drag
+            .as_ref()
+            .map(|drag| drag.handle)
+            .or(self.hovered)?;
+
+        elements
+            .filter(|x| x.id() == id)
+            .find_map(|x| x.handles().into_iter().nth(index))
+            .map(|handle| handle.cursor)
+    }
+
+    /// Returns the handle closest to `mouse_pos` within reach together with its position.
+    fn handle_at<'a>(
+        &self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Option<(HandleId, Coordinate<f64>)>
+    where
+        T: 'a,
+    {
+        let reach = (HANDLE_SIZE / 2. + HANDLE_TOLERANCE) / self.view_scale.get();
+
+        elements
+            .flat_map(|x| {
+                let id = x.id();
+                x.handles()
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(index, handle)| ((id, index), handle.position))
+            })
+            .map(|(handle, position)| (position.euclidean_distance(&mouse_pos), handle, position))
+            .filter(|(distance, _, _)| *distance <= reach)
+            .min_by(|(a, _, _), (b, _, _)| a.total_cmp(b))
+            .map(|(_, handle, position)| (handle, position))
+    }
+
+    /// Starts to drag the handle at `mouse_pos`. Returns true if a handle was hit.
+    pub fn mouse_down<'a>(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) -> bool
+    where
+        T: 'a,
+    {
+        let elements: Vec<&mut T> = elements.collect();
+
+        self.drag = self
+            .handle_at(mouse_pos, elements.iter().map(|x| &**x))
+            .and_then(|((id, index), position)| {
+                let element = elements.iter().find(|x| x.id() == id)?;
+                let handle = element.handles().into_iter().nth(index)?;
+
+                Some(Drag {
+                    handle: (id, index),
+                    name: handle.name,
+                    grab_offset: mouse_pos - position,
+                    before: (**element).clone(),
+                })
+            });
+
+        self.is_active()
+    }
+
+    /// Drags the grabbed handle to `mouse_pos` or, if no handle is dragged, updates the hovered handle.
+    pub fn mouse_move<'a>(
+        &mut self,
+        mouse_pos: Coordinate<f64>,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) where
+        T: 'a,
+    {
+        let drag = match self.drag.as_mut() {
+            Some(drag) => drag,
+            None => {
+                let elements: Vec<&mut T> = elements.collect();
+                self.hovered = self
+                    .handle_at(mouse_pos, elements.iter().map(|x| &**x))
+                    .map(|(handle, _)| handle);
+                return;
+            }
+        };
+
+        let mut target = mouse_pos - drag.grab_offset;
+        self.snap_hint = None;
+        if let Some(snapping) = &self.snapping {
+            let snapped = snapping
+                .borrow()
+                .snap_at_scale(target, self.view_scale.get());
+            target = snapped.point;
+            self.snap_hint = snapped.hint;
+        }
+
+        let (id, index) = drag.handle;
+        for x in elements.filter(|x| x.id() == id) {
+            if let Some(handle) = x.handles().into_iter().nth(index) {
+                (handle.on_drag)(x, target);
+            }
+        }
+    }
+
+    /// Ends the drag. Returns an action holding the dragged element before and after the drag, `None` if no handle
+    /// was dragged or the element did not change.
+    pub fn mouse_up<'a>(
+        &mut self,
+        elements: impl Iterator<Item = &'a mut T>,
+    ) -> Option<HandleAction<T>>
+    where
+        T: PartialEq + 'a,
+    {
+        self.snap_hint = None;
+
+        let drag = self.drag.take()?;
+        let (id, _) = drag.handle;
+        let after = elements
+            .into_iter()
+            .find(|x| x.id() == id)
+            .filter(|x| **x != drag.before)?
+            .clone();
+
+        Some(HandleAction {
+            id,
+            name: drag.name,
+            before: drag.before,
+            after,
+        })
+    }
+
+    pub fn render<'a>(
+        &self,
+        context: &CanvasRenderingContext2d,
+        elements: impl Iterator<Item = &'a T>,
+    ) -> Result<(), JsValue>
+    where
+        T: 'a,
+    {
+        self.view_scale.set(view_scale(context));
+
+        let dragged = self.drag.as_ref().map(|drag| drag.handle);
+        for x in elements {
+            let id = x.id();
+            for (index, handle) in x.handles().into_iter().enumerate() {
+                let style = if dragged == Some((id, index)) {
+                    &self.style.selected
+                } else if dragged.is_none() && self.hovered == Some((id, index)) {
+                    &self.style.hover
+                } else {
+                    &self.style.normal
+                };
+
+                draw_in_screen_space(context, handle.position, || {
+                    render_shape(handle.shape, style, context)
+                })?;
+            }
+        }
+
+        if let Some(hint) = &self.snap_hint {
+            hint.render(context)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws `shape` centered at the origin.
+fn render_shape(
+    shape: HandleShape,
+    style: &Style,
+    context: &CanvasRenderingContext2d,
+) -> Result<(), JsValue> {
+    let half = HANDLE_SIZE / 2.;
+
+    context.begin_path();
+    match shape {
+        HandleShape::Circle => context.arc(0., 0., half, 0., 2. * PI)?,
+        HandleShape::Square => context.rect(-half, -half, HANDLE_SIZE, HANDLE_SIZE),
+        HandleShape::Diamond => {
+            context.move_to(0., -half);
+            context.line_to(half, 0.);
+            context.line_to(0., half);
+            context.line_to(-half, 0.);
+            context.close_path();
+        }
+    }
+    apply_style(style, context);
+
+    Ok(())
+}
+
+/// Emitted by the [HandleEditor] once a drag ended. Undoing it restores the whole element.
+pub struct HandleAction<T> {
+    id: Uuid,
+    name: &'static str,
+    before: T,
+    after: T,
+}
+
+impl<T> HandleAction<T> {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn before(&self) -> &T {
+        &self.before
+    }
+
+    pub fn after(&self) -> &T {
+        &self.after
+    }
+}
+
+impl<Data, T> Undo<Data> for HandleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Clone,
+{
+    fn undo(&mut self, data: &mut Data) {
+        if let Some(element) = data.element_by_id_mut(&self.id) {
+            *element = self.before.clone();
+        }
+    }
+}
+
+impl<Data, T> Redo<Data> for HandleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Clone,
+{
+    fn redo(&mut self, data: &mut Data) {
+        if let Some(element) = data.element_by_id_mut(&self.id) {
+            *element = self.after.clone();
+        }
+    }
+}
+
+impl<Data, T> Action<Data> for HandleAction<T>
+where
+    Data: ElementsById<T>,
+    T: Clone + Send + Sync,
+{
+}
+
+impl<T> fmt::Display for HandleAction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Edit {}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::Coordinate;
+    use uuid::Uuid;
+
+    use super::{Handle, HandleEditor, HandleShape, Handles};
+    use crate::{
+        actions::Undo,
+        gizmo::{ElementsById, Id},
+    };
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct RoundedRect {
+        id: Uuid,
+        origin: Coordinate<f64>,
+        width: f64,
+        radius: f64,
+    }
+
+    impl Id for RoundedRect {
+        fn id(&self) -> Uuid {
+            self.id
+        }
+    }
+
+    impl Handles for RoundedRect {
+        fn handles(&self) -> Vec<Handle<Self>> {
+            let origin = self.origin;
+            let width = self.width;
+
+            vec![Handle::new(
+                "corner radius",
+                Coordinate {
+                    x: origin.x + self.radius,
+                    y: origin.y,
+                },
+                move |x: &mut RoundedRect, position| {
+                    x.radius = (position.x - origin.x).clamp(0., width / 2.)
+                },
+            )
+            .with_shape(HandleShape::Diamond)
+            .with_cursor("ew-resize")]
+        }
+    }
+
+    struct Map(Vec<RoundedRect>);
+
+    impl ElementsById<RoundedRect> for Map {
+        fn element_by_id_mut(&mut self, id: &Uuid) -> Option<&mut RoundedRect> {
+            self.0.iter_mut().find(|x| x.id == *id)
+        }
+    }
+
+    #[test]
+    fn handles_edit_elements() {
+        let mut map = Map(vec![RoundedRect {
+            id: Uuid::new_v4(),
+            origin: Coordinate { x: 0., y: 0. },
+            width: 100.,
+            radius: 10.,
+        }]);
+        let mut editor = HandleEditor::new();
+
+        // Hovering shows the cursor of the handle
+        editor.mouse_move(Coordinate { x: 12., y: 3. }, map.0.iter_mut());
+        assert_eq!(editor.cursor(map.0.iter()), Some("ew-resize"));
+
+        assert!(!editor.mouse_down(Coordinate { x: 50., y: 50. }, map.0.iter_mut()));
+        // Dragging the handle back to where it started changes nothing
+        assert!(editor.mouse_down(Coordinate { x: 12., y: 3. }, map.0.iter_mut()));
+        editor.mouse_move(Coordinate { x: 32., y: 10. }, map.0.iter_mut());
+        editor.mouse_move(Coordinate { x: 12., y: 3. }, map.0.iter_mut());
+        assert!(editor.mouse_up(map.0.iter_mut()).is_none());
+
+        assert!(editor.mouse_down(Coordinate { x: 12., y: 3. }, map.0.iter_mut()));
+        editor.mouse_move(Coordinate { x: 32., y: 10. }, map.0.iter_mut());
+        editor.mouse_move(Coordinate { x: 82., y: 10. }, map.0.iter_mut());
+        assert_eq!(map.0[0].radius, 50.);
+
+        let mut action = editor
+            .mouse_up(map.0.iter_mut())
+            .expect("a drag should emit an action");
+        assert_eq!(action.to_string(), "Edit corner radius");
+
+        action.undo(&mut map);
+        assert_eq!(map.0[0].radius, 10.);
+    }
+}
@@ -0,0 +1,666 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::actions::Action;
+
+/// Describes what the history is currently able to do. Use [History::subscribe] to get notified about changes, e.g. to
+/// enable or disable ui elements.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct HistoryState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+
+    /// Summary of the action that is undone next, see [Action::summary]
+    pub next_undo: Option<String>,
+
+    /// Summary of the action that is redone next, see [Action::summary]
+    pub next_redo: Option<String>,
+}
+
+type ActionRef<Data> = Rc<RefCell<dyn Action<Data>>>;
+type Observer = Box<dyn Fn(&HistoryState)>;
+type Listener<Data> = Box<dyn Fn(&HistoryEvent<Data>)>;
+type Applier<Data> = Box<dyn Fn(&HistoryEvent<Data>, &mut Data)>;
+
+/// Describes a change of the data made through the history, see [History::listen].
+pub enum HistoryEvent<'a, Data> {
+    /// The action was executed and pushed to the history. It is reported even if the history refused it.
+    Executed(&'a Rc<RefCell<dyn Action<Data>>>),
+    Undone(&'a Rc<RefCell<dyn Action<Data>>>),
+    Redone(&'a Rc<RefCell<dyn Action<Data>>>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryNodeState {
+    /// The action is applied to the data
+    Past,
+
+    /// The action is the most recent one that is applied to the data
+    Current,
+
+    /// The action can be redone
+    Future,
+
+    /// The action is part of a branch that is currently not followed
+    Branch,
+}
+
+/// Snapshot of an action within the undo tree, see [History::tree].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryNode {
+    pub label: String,
+    pub state: HistoryNodeState,
+
+    /// Actions that were executed right after this one. The first child continues the currently followed branch.
+    pub children: Vec<HistoryNode>,
+}
+
+/// A sequence of actions that was undone and then replaced by executing another action.
+struct Branch<Data> {
+    /// Action after which the branch forks off or `None` if it forks off at the initial state
+    parent: Option<ActionRef<Data>>,
+
+    /// Ordered from the oldest to the newest action
+    actions: Vec<ActionRef<Data>>,
+}
+
+/// Keeps track of all executed actions so that they can be undone and redone.
+///
+/// Both stacks are ordered from the oldest to the newest action, so the last element of the undo stack is the next action
+/// to undo and the last element of the redo stack is the next action to redo.
+///
+/// By default, executing an action after undoing discards everything that could have been redone. With branching enabled
+/// the discarded actions are kept as a branch of an undo tree instead, see [History::set_branching].
+pub struct History<Data> {
+    undo_stack: Vec<Rc<RefCell<dyn Action<Data>>>>,
+    redo_stack: Vec<Rc<RefCell<dyn Action<Data>>>>,
+
+    branching: bool,
+    branches: Vec<Branch<Data>>,
+
+    observers: Vec<Observer>,
+    listeners: Vec<Listener<Data>>,
+
+    /// Undoes and redoes actions instead of the history, see [History::set_applier]
+    applier: Option<Applier<Data>>,
+}
+
+impl<Data> Default for History<Data> {
+    fn default() -> Self {
+        History {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            branching: false,
+            branches: Vec::new(),
+            observers: Vec::new(),
+            listeners: Vec::new(),
+            applier: None,
+        }
+    }
+}
+
+/// Compares two actions by identity.
+fn same<Data>(a: Option<&ActionRef<Data>>, b: Option<&ActionRef<Data>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Rc::as_ptr(a) as *const () == Rc::as_ptr(b) as *const (),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl<Data> History<Data> {
+    /// Adds an already executed action to the history. All actions that could be redone so far are discarded or, if
+    /// branching is enabled, kept as a new branch.
+    ///
+    /// Actions that are not undoable are refused. Since the data was changed by them, the actions recorded so far cannot
+    /// be undone reliably anymore and the history is cleared instead.
+    pub fn push<S>(&mut self, action: Rc<RefCell<S>>)
+    where
+        S: Action<Data> + 'static,
+    {
+        self.push_generic(action);
+    }
+
+    pub fn push_generic(&mut self, action: Rc<RefCell<dyn Action<Data>>>) {
+        self.emit(&HistoryEvent::Executed(&action));
+
+        if !action.borrow().is_undoable() {
+            self.clear();
+            return;
+        }
+
+        if self.branching && !self.redo_stack.is_empty() {
+            let actions = self.redo_stack.drain(..).rev().collect();
+            self.branches.push(Branch {
+                parent: self.undo_stack.last().cloned(),
+                actions,
+            });
+        }
+
+        self.undo_stack.push(action);
+        self.redo_stack.clear();
+        self.notify();
+    }
+
+    pub fn is_branching(&self) -> bool {
+        self.branching
+    }
+
+    /// Enables or disables keeping undone actions as branches. Disabling it drops all branches that are not followed.
+    pub fn set_branching(&mut self, branching: bool) {
+        self.branching = branching;
+
+        if !branching {
+            self.branches.clear();
+        }
+    }
+
+    /// Returns true if there is an action to undo and it is undoable.
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack
+            .last()
+            .is_some_and(|action| action.borrow().is_undoable())
+    }
+
+    /// Returns true if there is an action to redo and it is redoable.
+    pub fn can_redo(&self) -> bool {
+        self.redo_stack
+            .last()
+            .is_some_and(|action| action.borrow().is_redoable())
+    }
+
+    /// Undoes the most recent action. Returns false if there is nothing to undo or the action refuses to be undone.
+    pub fn undo(&mut self, data: &mut Data) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+
+        let action = self.undo_stack.pop().unwrap();
+        let event = HistoryEvent::Undone(&action);
+        match &self.applier {
+            Some(applier) => applier(&event, data),
+            None => action.borrow_mut().undo(data),
+        }
+        self.emit(&event);
+        self.redo_stack.push(action);
+        self.notify();
+
+        true
+    }
+
+    /// Redoes the most recently undone action. Returns false if there is nothing to redo or the action refuses to be redone.
+    pub fn redo(&mut self, data: &mut Data) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+
+        let action = self.redo_stack.pop().unwrap();
+        let event = HistoryEvent::Redone(&action);
+        match &self.applier {
+            Some(applier) => applier(&event, data),
+            None => action.borrow_mut().redo(data),
+        }
+        self.emit(&event);
+        self.undo_stack.push(action);
+        self.notify();
+
+        true
+    }
+
+    /// Undoes or redoes actions until exactly `position` actions are applied to the data.
+    pub fn jump_to(&mut self, position: usize, data: &mut Data) {
+        while self.position() > position && self.undo(data) {}
+        while self.position() < position && self.redo(data) {}
+    }
+
+    /// Returns the number of actions that are currently applied to the data.
+    pub fn position(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn undo_stack(&self) -> &[Rc<RefCell<dyn Action<Data>>>] {
+        &self.undo_stack
+    }
+
+    pub fn redo_stack(&self) -> &[Rc<RefCell<dyn Action<Data>>>] {
+        &self.redo_stack
+    }
+
+    /// Undoes or redoes actions until the action at `path` is the most recent applied one. Each element of `path` is the
+    /// index of a child as returned by [History::tree], an empty path refers to the initial state.
+    ///
+    /// Returns false if the path does not exist or an action on the way refused to be undone or redone.
+    pub fn jump_to_node(&mut self, path: &[usize], data: &mut Data) -> bool {
+        let mut target: Vec<ActionRef<Data>> = Vec::with_capacity(path.len());
+        for index in path {
+            match self.children(target.last()).get(*index) {
+                Some(child) => target.push(Rc::clone(child)),
+                None => return false,
+            }
+        }
+
+        let common = self
+            .active_path()
+            .iter()
+            .zip(target.iter())
+            .take_while(|(a, b)| same(Some(a), Some(b)))
+            .count();
+
+        while self.position() > common {
+            if !self.undo(data) {
+                return false;
+            }
+        }
+
+        while self.position() < target.len() {
+            let next = Rc::clone(&target[self.position()]);
+            if !self.redo_into(&next, data) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Replaces the most recent applied action with its next (`forward`) or previous sibling, i.e. another action that
+    /// was executed at the same state. Returns false if there is no sibling.
+    pub fn switch_sibling(&mut self, forward: bool, data: &mut Data) -> bool {
+        let current = match self.undo_stack.last() {
+            Some(current) => Rc::clone(current),
+            None => return false,
+        };
+
+        let parent = self
+            .undo_stack
+            .len()
+            .checked_sub(2)
+            .map(|index| Rc::clone(&self.undo_stack[index]));
+        let siblings = self.children(parent.as_ref());
+        if siblings.len() < 2 {
+            return false;
+        }
+
+        let index = siblings
+            .iter()
+            .position(|sibling| same(Some(sibling), Some(&current)))
+            .unwrap_or(0);
+        let sibling = match forward {
+            true => &siblings[(index + 1) % siblings.len()],
+            false => &siblings[(index + siblings.len() - 1) % siblings.len()],
+        };
+
+        // The replaced branch is stored behind the other siblings when switching forward and in front of them when
+        // switching backward, so that switching repeatedly into the same direction visits all of them
+        let sibling = Rc::clone(sibling);
+        self.undo(data) && self.follow_branch(&sibling, forward) && self.redo(data)
+    }
+
+    /// Returns the actions that were executed at the initial state together with everything that followed them.
+    pub fn tree(&self) -> Vec<HistoryNode> {
+        self.subtree(None, &self.active_path())
+    }
+
+    fn subtree(
+        &self,
+        node: Option<&ActionRef<Data>>,
+        active: &[ActionRef<Data>],
+    ) -> Vec<HistoryNode> {
+        let position = self.position();
+
+        self.children_in(node, active)
+            .iter()
+            .map(|child| {
+                let state = match active.iter().position(|a| same(Some(a), Some(child))) {
+                    Some(index) if index + 1 < position => HistoryNodeState::Past,
+                    Some(index) if index + 1 == position => HistoryNodeState::Current,
+                    Some(_) => HistoryNodeState::Future,
+                    None => HistoryNodeState::Branch,
+                };
+
+                HistoryNode {
+                    label: child.borrow().to_string(),
+                    state,
+                    children: self.subtree(Some(child), active),
+                }
+            })
+            .collect()
+    }
+
+    /// All actions of the currently followed branch from the oldest to the newest, no matter if applied or not.
+    fn active_path(&self) -> Vec<ActionRef<Data>> {
+        self.undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the actions that were executed right after `node`. The action of the currently followed branch comes
+    /// first, followed by the first actions of all branches that fork off at `node`.
+    fn children(&self, node: Option<&ActionRef<Data>>) -> Vec<ActionRef<Data>> {
+        self.children_in(node, &self.active_path())
+    }
+
+    fn children_in(
+        &self,
+        node: Option<&ActionRef<Data>>,
+        active: &[ActionRef<Data>],
+    ) -> Vec<ActionRef<Data>> {
+        let next = match node {
+            None => active.first().cloned(),
+            Some(node) => std::iter::once(active)
+                .chain(self.branches.iter().map(|branch| &branch.actions[..]))
+                .find_map(|actions| {
+                    actions
+                        .iter()
+                        .position(|action| same(Some(action), Some(node)))
+                        .and_then(|index| actions.get(index + 1).cloned())
+                }),
+        };
+
+        next.into_iter()
+            .chain(
+                self.branches
+                    .iter()
+                    .filter(|branch| same(branch.parent.as_ref(), node))
+                    .map(|branch| Rc::clone(&branch.actions[0])),
+            )
+            .collect()
+    }
+
+    /// Makes the branch that starts with `next` the one that is redone next. The branch that was followed so far is
+    /// stored behind (`append`) or in front of the other branches that fork off at the same action.
+    fn follow_branch(&mut self, next: &ActionRef<Data>, append: bool) -> bool {
+        if same(self.redo_stack.last(), Some(next)) {
+            return true;
+        }
+
+        let parent = self.undo_stack.last().cloned();
+        let index = match self.branches.iter().position(|branch| {
+            same(branch.parent.as_ref(), parent.as_ref())
+                && same(branch.actions.first(), Some(next))
+        }) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let branch = self.branches.remove(index);
+        if !self.redo_stack.is_empty() {
+            let followed = Branch {
+                parent: parent.clone(),
+                actions: self.redo_stack.drain(..).rev().collect(),
+            };
+
+            match append {
+                true => self.branches.push(followed),
+                false => {
+                    let first = self
+                        .branches
+                        .iter()
+                        .position(|branch| same(branch.parent.as_ref(), parent.as_ref()))
+                        .unwrap_or(self.branches.len());
+                    self.branches.insert(first, followed);
+                }
+            }
+        }
+
+        self.redo_stack = branch.actions.into_iter().rev().collect();
+        true
+    }
+
+    fn redo_into(&mut self, next: &ActionRef<Data>, data: &mut Data) -> bool {
+        self.follow_branch(next, true) && self.redo(data)
+    }
+
+    /// Replaces both stacks without executing any of the actions.
+    pub fn replace(
+        &mut self,
+        undo_stack: Vec<Rc<RefCell<dyn Action<Data>>>>,
+        redo_stack: Vec<Rc<RefCell<dyn Action<Data>>>>,
+    ) {
+        self.undo_stack = undo_stack;
+        self.redo_stack = redo_stack;
+        self.branches.clear();
+        self.notify();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.branches.clear();
+        self.notify();
+    }
+
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            can_undo: self.can_undo(),
+            can_redo: self.can_redo(),
+            next_undo: self
+                .undo_stack
+                .last()
+                .map(|action| action.borrow().summary()),
+            next_redo: self
+                .redo_stack
+                .last()
+                .map(|action| action.borrow().summary()),
+        }
+    }
+
+    /// Registers `observer` to be called with the new state each time the history changes. It is called once
+    /// immediately with the current state.
+    pub fn subscribe(&mut self, observer: impl Fn(&HistoryState) + 'static) {
+        observer(&self.state());
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Registers `listener` to be called each time an action is executed, undone or redone through the history.
+    /// Replacing or clearing the history is not reported.
+    pub fn listen(&mut self, listener: impl Fn(&HistoryEvent<Data>) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Lets `applier` undo and redo actions instead of calling [Undo::undo](crate::actions::Undo::undo) and
+    /// [Redo::redo](crate::actions::Redo::redo) on them. It receives [HistoryEvent::Undone] and [HistoryEvent::Redone]
+    /// before the listeners do. Needed if the data contains changes that are not part of the history, e.g. of other users
+    /// editing the same data, which have to be kept when an older action is undone.
+    pub fn set_applier(&mut self, applier: impl Fn(&HistoryEvent<Data>, &mut Data) + 'static) {
+        self.applier = Some(Box::new(applier));
+    }
+
+    pub fn remove_applier(&mut self) {
+        self.applier = None;
+    }
+
+    fn emit(&self, event: &HistoryEvent<Data>) {
+        for listener in &self.listeners {
+            listener(event);
+        }
+    }
+
+    fn notify(&self) {
+        if self.observers.is_empty() {
+            return;
+        }
+
+        let state = self.state();
+        for observer in &self.observers {
+            observer(&state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fmt, rc::Rc};
+
+    use super::{History, HistoryNodeState, HistoryState};
+    use crate::actions::{Action, Redo, Undo};
+
+    struct Add(i32);
+
+    impl fmt::Display for Add {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Add {}", self.0)
+        }
+    }
+
+    impl Undo<i32> for Add {
+        fn undo(&mut self, map: &mut i32) {
+            *map -= self.0;
+        }
+    }
+
+    impl Redo<i32> for Add {
+        fn redo(&mut self, map: &mut i32) {
+            *map += self.0;
+        }
+    }
+
+    impl Action<i32> for Add {}
+
+    /// Can be executed but never be undone
+    struct Reset;
+
+    impl fmt::Display for Reset {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Reset")
+        }
+    }
+
+    impl Undo<i32> for Reset {
+        fn undo(&mut self, _: &mut i32) {}
+
+        fn is_undoable(&self) -> bool {
+            false
+        }
+    }
+
+    impl Redo<i32> for Reset {
+        fn redo(&mut self, map: &mut i32) {
+            *map = 0;
+        }
+    }
+
+    impl Action<i32> for Reset {}
+
+    fn history_with(values: &[i32], data: &mut i32) -> History<i32> {
+        let mut history = History::default();
+        for value in values {
+            let mut action = Add(*value);
+            action.execute(data);
+            history.push(Rc::new(RefCell::new(action)));
+        }
+
+        history
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut data = 0;
+        let mut history = history_with(&[1, 2], &mut data);
+
+        assert!(history.undo(&mut data));
+        assert_eq!(data, 1);
+        assert_eq!(history.redo_stack().len(), 1);
+
+        assert!(history.redo(&mut data));
+        assert_eq!(data, 3);
+        assert!(!history.redo(&mut data));
+    }
+
+    #[test]
+    fn push_discards_redo_stack() {
+        let mut data = 0;
+        let mut history = history_with(&[1, 2], &mut data);
+
+        history.undo(&mut data);
+        history.push(Rc::new(RefCell::new(Add(4))));
+
+        assert!(history.redo_stack().is_empty());
+    }
+
+    #[test]
+    fn jump_to() {
+        let mut data = 0;
+        let mut history = history_with(&[1, 2, 4], &mut data);
+
+        history.jump_to(1, &mut data);
+        assert_eq!(data, 1);
+        assert_eq!(history.position(), 1);
+
+        history.jump_to(3, &mut data);
+        assert_eq!(data, 7);
+
+        history.jump_to(10, &mut data);
+        assert_eq!(history.position(), 3);
+    }
+
+    #[test]
+    fn refuses_actions_that_are_not_undoable() {
+        let mut data = 0;
+        let mut history = history_with(&[1, 2], &mut data);
+
+        let mut action = Reset;
+        action.execute(&mut data);
+        history.push(Rc::new(RefCell::new(action)));
+
+        assert_eq!(history.position(), 0);
+        assert!(!history.undo(&mut data));
+        assert_eq!(data, 0);
+    }
+
+    #[test]
+    fn notifies_subscribers() {
+        let mut data = 0;
+        let mut history = history_with(&[1], &mut data);
+
+        let state = Rc::new(RefCell::new(HistoryState::default()));
+        let observed = Rc::clone(&state);
+        history.subscribe(move |new_state| *observed.borrow_mut() = new_state.clone());
+        assert_eq!(state.borrow().next_undo, Some("Add 1".to_string()));
+
+        history.undo(&mut data);
+        assert!(!state.borrow().can_undo);
+        assert!(state.borrow().can_redo);
+        assert_eq!(state.borrow().next_redo, Some("Add 1".to_string()));
+    }
+
+    #[test]
+    fn branching_keeps_undone_actions() {
+        let mut data = 0;
+        let mut history = History::default();
+        history.set_branching(true);
+
+        for value in [1, 2] {
+            let mut action = Add(value);
+            action.execute(&mut data);
+            history.push(Rc::new(RefCell::new(action)));
+        }
+
+        history.undo(&mut data);
+        let mut action = Add(4);
+        action.execute(&mut data);
+        history.push(Rc::new(RefCell::new(action)));
+        assert_eq!(data, 5);
+
+        let tree = history.tree();
+        assert_eq!(tree.len(), 1);
+        let children = &tree[0].children;
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].label, "Add 4");
+        assert_eq!(children[0].state, HistoryNodeState::Current);
+        assert_eq!(children[1].label, "Add 2");
+        assert_eq!(children[1].state, HistoryNodeState::Branch);
+
+        assert!(history.jump_to_node(&[0, 1], &mut data));
+        assert_eq!(data, 3);
+
+        assert!(history.switch_sibling(true, &mut data));
+        assert_eq!(data, 5);
+        assert!(history.switch_sibling(false, &mut data));
+        assert_eq!(data, 3);
+
+        assert!(history.jump_to_node(&[], &mut data));
+        assert_eq!(data, 0);
+        assert!(!history.jump_to_node(&[3], &mut data));
+    }
+}
@@ -0,0 +1,245 @@
+use std::{error::Error, rc::Rc};
+
+use js_sys::Uint8Array;
+use thiserror::Error;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::File;
+
+use crate::ui::app::App;
+
+/// Content of a file that was dropped onto the editor or chosen with [App::choose_import_files].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedFile {
+    pub name: String,
+
+    /// Mime type reported by the browser, empty if it is unknown
+    pub mime_type: String,
+
+    pub bytes: Vec<u8>,
+}
+
+impl ImportedFile {
+    /// Returns the lowercase extension of the file name without the dot, e.g. `svg` for `Map.SVG`.
+    pub fn extension(&self) -> Option<String> {
+        extension(&self.name)
+    }
+
+    /// Interprets the content as utf-8 text.
+    pub fn text(&self) -> Result<&str, std::str::Utf8Error> {
+        std::str::from_utf8(&self.bytes)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("{name}: no importer is registered for this file type")]
+    Unsupported { name: String },
+
+    #[error("{name}: could not read file: {reason}")]
+    Read { name: String, reason: String },
+
+    #[error("{name}: {source}")]
+    Failed {
+        name: String,
+        source: Box<dyn Error>,
+    },
+}
+
+/// Describes the outcome of importing a single file, see [App::listen_imports].
+pub enum ImportEvent<'a> {
+    /// The file was imported by the importer with the given name
+    Imported {
+        file: &'a str,
+        importer: &'a str,
+    },
+    Failed(&'a ImportError),
+}
+
+/// Adds the content of a file to the data of the editor.
+///
+/// The importer runs inside a transaction, so all actions it pushes become one undoable step. If it fails, the
+/// transaction is rolled back.
+pub type Importer<Data> = Rc<dyn Fn(&ImportedFile, &mut App<Data>) -> Result<(), Box<dyn Error>>>;
+
+struct Registration<Data>
+where
+    Data: Default + 'static,
+{
+    name: &'static str,
+    mime_types: Vec<String>,
+    extensions: Vec<String>,
+    importer: Importer<Data>,
+}
+
+type Listener = Box<dyn Fn(&ImportEvent)>;
+
+/// Knows which importer handles which files, see [App::register_importer].
+pub struct ImportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    registrations: Vec<Registration<Data>>,
+    listeners: Vec<Listener>,
+}
+
+impl<Data> Default for ImportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    fn default() -> Self {
+        ImportRegistry {
+            registrations: Vec::new(),
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl<Data> ImportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    /// Registers `importer` for files of the given mime types or extensions. Mime types may end with a wildcard, e.g.
+    /// `image/*`, extensions are given without the dot. Importers registered first take precedence.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        mime_types: &[&str],
+        extensions: &[&str],
+        importer: Importer<Data>,
+    ) {
+        self.registrations.push(Registration {
+            name,
+            mime_types: mime_types.iter().map(|s| s.to_lowercase()).collect(),
+            extensions: extensions
+                .iter()
+                .map(|s| s.trim_start_matches('.').to_lowercase())
+                .collect(),
+            importer,
+        });
+    }
+
+    /// Returns the name and importer that handles `file`. Importers registered for the mime type of the file are
+    /// preferred over the ones registered for its extension, since browsers report a mime type only for well known
+    /// file types.
+    pub fn find(&self, file: &ImportedFile) -> Option<(&'static str, Importer<Data>)> {
+        let mime_type = file.mime_type.to_lowercase();
+        let extension = file.extension();
+
+        self.registrations
+            .iter()
+            .find(|registration| {
+                !mime_type.is_empty()
+                    && registration
+                        .mime_types
+                        .iter()
+                        .any(|pattern| matches_mime_type(pattern, &mime_type))
+            })
+            .or_else(|| {
+                let extension = extension.as_ref()?;
+                self.registrations
+                    .iter()
+                    .find(|registration| registration.extensions.contains(extension))
+            })
+            .map(|registration| (registration.name, Rc::clone(&registration.importer)))
+    }
+
+    /// Returns the value for the `accept` attribute of a file input, which lists all supported file types.
+    pub fn accept(&self) -> String {
+        let mut types: Vec<String> = Vec::new();
+        for registration in &self.registrations {
+            let extensions = registration.extensions.iter().map(|e| format!(".{}", e));
+
+            for file_type in registration.mime_types.iter().cloned().chain(extensions) {
+                if !types.contains(&file_type) {
+                    types.push(file_type);
+                }
+            }
+        }
+
+        types.join(",")
+    }
+
+    pub fn listen(&mut self, listener: impl Fn(&ImportEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    pub(crate) fn emit(&self, event: &ImportEvent) {
+        for listener in &self.listeners {
+            listener(event);
+        }
+    }
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    match stem.is_empty() || extension.is_empty() {
+        true => None,
+        false => Some(extension.to_lowercase()),
+    }
+}
+
+fn matches_mime_type(pattern: &str, mime_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(group) => {
+            matches!(mime_type.split_once('/'), Some((mime_group, _)) if mime_group == group)
+        }
+        None => pattern == mime_type,
+    }
+}
+
+/// Reads the content of `file` into memory.
+pub async fn read_file(file: File) -> Result<ImportedFile, ImportError> {
+    let name = file.name();
+
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|e| ImportError::Read {
+            name: name.clone(),
+            reason: format!("{:?}", e),
+        })?;
+
+    Ok(ImportedFile {
+        name,
+        mime_type: file.type_(),
+        bytes: Uint8Array::new(&buffer).to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{ImportRegistry, ImportedFile};
+
+    fn file(name: &str, mime_type: &str) -> ImportedFile {
+        ImportedFile {
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            bytes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn importers_are_found_by_mime_type_and_extension() {
+        let mut registry = ImportRegistry::<()>::default();
+        registry.register("image", &["image/*"], &[], Rc::new(|_, _| Ok(())));
+        registry.register("geojson", &[], &["GeoJSON"], Rc::new(|_, _| Ok(())));
+        registry.register(
+            "json",
+            &["application/json"],
+            &["json"],
+            Rc::new(|_, _| Ok(())),
+        );
+
+        let find = |file: ImportedFile| registry.find(&file).map(|(name, _)| name);
+
+        assert_eq!(find(file("map.png", "image/png")), Some("image"));
+        assert_eq!(find(file("Berlin.geojson", "")), Some("geojson"));
+        assert_eq!(find(file("data.JSON", "")), Some("json"));
+        assert_eq!(find(file("data", "application/json")), Some("json"));
+        assert_eq!(find(file("notes.txt", "text/plain")), None);
+        assert_eq!(find(file(".json", "")), None);
+
+        assert_eq!(registry.accept(), "image/*,.geojson,application/json,.json");
+    }
+}
@@ -171,7 +171,7 @@ impl From<String> for Key {
             "Unknown" => Key::Unknown,
             "BACKSPACE" => Key::Backspace,
             "TAB" => Key::Tab,
-            "RETURN" => Key::Return,
+            "ENTER" | "RETURN" => Key::Return,
             "ESCAPE" => Key::Escape,
             " " => Key::Space,
             "Exclaim" => Key::Exclaim,
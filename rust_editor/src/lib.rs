@@ -1,8 +1,14 @@
 pub mod actions;
+pub mod export;
 pub mod gizmo;
+pub mod handle;
+pub mod history;
+pub mod import;
 pub mod interactive_element;
 pub mod macros;
 pub mod renderer;
+pub mod snapshot;
+pub mod snapping;
 pub mod store;
 pub mod style;
 pub mod system;
@@ -112,8 +112,13 @@ where
         false
     }
 
+    /// Renders the data. Everything rendered here is part of exported images and thumbnails.
     fn render(&self, context: &CanvasRenderingContext2d, editor: &App<Data>) {}
 
+    /// Renders ui that helps editing but does not belong to the data, e.g. gizmos, the selection or snapping hints. Called
+    /// after all plugins rendered and left out of exported images and thumbnails.
+    fn render_overlay(&self, context: &CanvasRenderingContext2d, editor: &App<Data>) {}
+
     fn editor_elements(&mut self, ctx: &Context<App<Data>>, editor: &App<Data>) -> Vec<Html> {
         Vec::default()
     }
@@ -165,8 +170,15 @@ where
 
     fn property_updated(&mut self, property: &str, editor: &mut App<Data>) {}
 
+    /// Receives a message that was send to the plugin with [PluginMessage](crate::ui::app::EditorMessages::PluginMessage).
     fn on_message(&mut self, message: Box<dyn Any>) {}
 
+    /// Like [on_message](Plugin::on_message), but with access to the editor. Use it to react on ui elements of the plugin
+    /// that need to alter the data hold by the editor. Calls [on_message](Plugin::on_message) by default.
+    fn on_message_with_editor(&mut self, message: Box<dyn Any>, editor: &mut App<Data>) {
+        self.on_message(message)
+    }
+
     /// Called once before the plugin is added to the editor list of plugins. You can use this it to add additional ui elements such as toolbars (and buttons) or
     /// register shortkeys for the plugin.
     fn startup(&mut self, editor: &mut App<Data>) -> Result<(), EditorError> {
@@ -9,6 +9,12 @@ use web_sys::CanvasRenderingContext2d;
 
 use crate::{style::Style};
 
+mod path;
+mod text;
+
+pub use self::path::{Path, PathSegment, SubPath};
+pub use self::text::{measure_text, Font, Text, TextAlign, TextBaseline, TextLayout, TextLine};
+
 pub fn apply_style(style: &Style, context: &CanvasRenderingContext2d) {
     context.set_fill_style(&style.background_color.clone().into());
     context.fill();
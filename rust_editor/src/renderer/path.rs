@@ -0,0 +1,433 @@
+use std::f64::consts::PI;
+
+use geo::{Coordinate, LineString, MultiLineString};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use super::{apply_style, PrimitiveRenderer};
+use crate::style::Style;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum PathSegment {
+    LineTo(Coordinate<f64>),
+
+    QuadraticTo {
+        control: Coordinate<f64>,
+        to: Coordinate<f64>,
+    },
+
+    CubicTo {
+        control1: Coordinate<f64>,
+        control2: Coordinate<f64>,
+        to: Coordinate<f64>,
+    },
+
+    /// Part of an ellipse, rotated by `rotation` radians around its center. The angles are measured in radians from
+    /// the x axis of the ellipse. Like on the canvas, a straight line connects the previous point with the start of
+    /// the arc.
+    Arc {
+        center: Coordinate<f64>,
+        radius: Coordinate<f64>,
+        rotation: f64,
+        start_angle: f64,
+        end_angle: f64,
+        counterclockwise: bool,
+    },
+}
+
+/// A connected sequence of segments.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SubPath {
+    pub start: Coordinate<f64>,
+    pub segments: Vec<PathSegment>,
+
+    /// Connects the last point with `start`
+    pub closed: bool,
+}
+
+/// Curved shape made of one or more subpaths, e.g. a connector between two elements.
+///
+/// Paths are built like on the canvas:
+///
+/// ```ignore
+/// let path = Path::new()
+///     .move_to(Coordinate { x: 0., y: 0. })
+///     .cubic_to(Coordinate { x: 50., y: 0. }, Coordinate { x: 50., y: 100. }, Coordinate { x: 100., y: 100. });
+/// ```
+///
+/// Closed subpaths are filled and their border is drawn like a [Polygon](geo::Polygon), open subpaths are rendered
+/// as lines like [Line](geo::Line).
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct Path {
+    pub subpaths: Vec<SubPath>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// Starts a new subpath at `point`.
+    pub fn move_to(mut self, point: Coordinate<f64>) -> Self {
+        self.subpaths.push(SubPath {
+            start: point,
+            segments: Vec::new(),
+            closed: false,
+        });
+        self
+    }
+
+    pub fn line_to(self, to: Coordinate<f64>) -> Self {
+        self.push(PathSegment::LineTo(to))
+    }
+
+    pub fn quadratic_to(self, control: Coordinate<f64>, to: Coordinate<f64>) -> Self {
+        self.push(PathSegment::QuadraticTo { control, to })
+    }
+
+    pub fn cubic_to(
+        self,
+        control1: Coordinate<f64>,
+        control2: Coordinate<f64>,
+        to: Coordinate<f64>,
+    ) -> Self {
+        self.push(PathSegment::CubicTo {
+            control1,
+            control2,
+            to,
+        })
+    }
+
+    /// Adds a circular arc, see [PathSegment::Arc].
+    pub fn arc(
+        self,
+        center: Coordinate<f64>,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        counterclockwise: bool,
+    ) -> Self {
+        self.ellipse(
+            center,
+            Coordinate {
+                x: radius,
+                y: radius,
+            },
+            0.,
+            start_angle,
+            end_angle,
+            counterclockwise,
+        )
+    }
+
+    /// Adds an elliptical arc, see [PathSegment::Arc].
+    pub fn ellipse(
+        self,
+        center: Coordinate<f64>,
+        radius: Coordinate<f64>,
+        rotation: f64,
+        start_angle: f64,
+        end_angle: f64,
+        counterclockwise: bool,
+    ) -> Self {
+        self.push(PathSegment::Arc {
+            center,
+            radius,
+            rotation,
+            start_angle,
+            end_angle,
+            counterclockwise,
+        })
+    }
+
+    /// Closes the current subpath. The next segment starts a new subpath at the same point.
+    pub fn close(mut self) -> Self {
+        if let Some(subpath) = self.subpaths.last_mut() {
+            subpath.closed = true;
+        }
+        self
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.subpaths.iter().any(|x| x.closed)
+    }
+
+    /// Approximates the path with straight lines that are never further than `tolerance` away from it, e.g. to
+    /// hit-test it or to use it with the algorithms of `geo`.
+    pub fn flatten(&self, tolerance: f64) -> MultiLineString<f64> {
+        MultiLineString(self.subpaths.iter().map(|x| x.flatten(tolerance)).collect())
+    }
+
+    /// Appends `segment` to the current subpath. Like on the canvas, a closed subpath is continued by a new one at
+    /// its start, and an empty path starts at the first point of the segment.
+    fn push(mut self, segment: PathSegment) -> Self {
+        let start = match self.subpaths.last() {
+            Some(subpath) if !subpath.closed => None,
+            Some(subpath) => Some(subpath.start),
+            None => Some(match &segment {
+                PathSegment::LineTo(to) => *to,
+                PathSegment::QuadraticTo { control, .. } => *control,
+                PathSegment::CubicTo { control1, .. } => *control1,
+                PathSegment::Arc {
+                    center,
+                    radius,
+                    rotation,
+                    start_angle,
+                    ..
+                } => ellipse_point(*center, *radius, *rotation, *start_angle),
+            }),
+        };
+        if let Some(start) = start {
+            self = self.move_to(start);
+        }
+
+        if let Some(subpath) = self.subpaths.last_mut() {
+            subpath.segments.push(segment);
+        }
+        self
+    }
+}
+
+impl SubPath {
+    /// Approximates the subpath with straight lines, see [Path::flatten]. The line string of a closed subpath ends
+    /// at its start.
+    pub fn flatten(&self, tolerance: f64) -> LineString<f64> {
+        let tolerance = tolerance.max(f64::EPSILON);
+        let mut points = vec![self.start];
+        let mut current = self.start;
+
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::LineTo(to) => points.push(to),
+                PathSegment::QuadraticTo { control, to } => {
+                    // The second derivative bounds how far the curve bends away from its chords
+                    let bend = length(current - control * 2. + to);
+                    let steps = steps((bend / (4. * tolerance)).sqrt());
+                    points.extend((1..=steps).map(|i| {
+                        let t = i as f64 / steps as f64;
+                        let u = 1. - t;
+                        current * (u * u) + control * (2. * u * t) + to * (t * t)
+                    }));
+                }
+                PathSegment::CubicTo {
+                    control1,
+                    control2,
+                    to,
+                } => {
+                    let bend = length(current - control1 * 2. + control2)
+                        .max(length(control1 - control2 * 2. + to));
+                    let steps = steps((3. * bend / (4. * tolerance)).sqrt());
+                    points.extend((1..=steps).map(|i| {
+                        let t = i as f64 / steps as f64;
+                        let u = 1. - t;
+                        current * (u * u * u)
+                            + control1 * (3. * u * u * t)
+                            + control2 * (3. * u * t * t)
+                            + to * (t * t * t)
+                    }));
+                }
+                PathSegment::Arc {
+                    center,
+                    radius,
+                    rotation,
+                    start_angle,
+                    end_angle,
+                    counterclockwise,
+                } => {
+                    let sweep = sweep(start_angle, end_angle, counterclockwise);
+                    let max_radius = radius.x.abs().max(radius.y.abs());
+
+                    // Largest angle whose chord stays within the tolerance
+                    let step_angle = match tolerance < max_radius {
+                        true => 2. * (1. - tolerance / max_radius).acos(),
+                        false => PI,
+                    };
+                    let steps = steps(sweep.abs() / step_angle);
+                    let start = ellipse_point(center, radius, rotation, start_angle);
+                    if start != current {
+                        points.push(start);
+                    }
+                    points.extend((1..=steps).map(|i| {
+                        let angle = start_angle + sweep * i as f64 / steps as f64;
+                        ellipse_point(center, radius, rotation, angle)
+                    }));
+                }
+            }
+
+            current = *points.last().unwrap_or(&current);
+        }
+
+        if self.closed && current != self.start {
+            points.push(self.start);
+        }
+
+        LineString::from(points)
+    }
+
+    /// Adds the subpath to the current path of `context`.
+    fn trace(&self, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        context.move_to(self.start.x, self.start.y);
+
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::LineTo(to) => context.line_to(to.x, to.y),
+                PathSegment::QuadraticTo { control, to } => {
+                    context.quadratic_curve_to(control.x, control.y, to.x, to.y)
+                }
+                PathSegment::CubicTo {
+                    control1,
+                    control2,
+                    to,
+                } => context
+                    .bezier_curve_to(control1.x, control1.y, control2.x, control2.y, to.x, to.y),
+                PathSegment::Arc {
+                    center,
+                    radius,
+                    rotation,
+                    start_angle,
+                    end_angle,
+                    counterclockwise,
+                } => context.ellipse_with_anticlockwise(
+                    center.x,
+                    center.y,
+                    radius.x.abs(),
+                    radius.y.abs(),
+                    rotation,
+                    start_angle,
+                    end_angle,
+                    counterclockwise,
+                )?,
+            }
+        }
+
+        if self.closed {
+            context.close_path();
+        }
+
+        Ok(())
+    }
+}
+
+impl PrimitiveRenderer for Path {
+    fn render(&self, style: &Style, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        let (closed, open): (Vec<_>, Vec<_>) = self.subpaths.iter().partition(|x| x.closed);
+
+        if !closed.is_empty() {
+            context.begin_path();
+            for subpath in closed {
+                subpath.trace(context)?;
+            }
+            apply_style(style, context);
+        }
+
+        if !open.is_empty() {
+            context.begin_path();
+            for subpath in open {
+                subpath.trace(context)?;
+            }
+            context.set_line_width(std::cmp::max(style.border_width, 1) as f64);
+            context.set_stroke_style_str(&style.background_color);
+            context.stroke();
+        }
+
+        Ok(())
+    }
+}
+
+fn length(vector: Coordinate<f64>) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+/// Returns the number of straight lines a curve is split into, at least one.
+fn steps(count: f64) -> usize {
+    match count.is_finite() {
+        true => (count.ceil() as usize).clamp(1, 1024),
+        false => 1,
+    }
+}
+
+/// Returns the signed angle an arc from `start_angle` to `end_angle` covers, following the rules of the canvas.
+fn sweep(start_angle: f64, end_angle: f64, counterclockwise: bool) -> f64 {
+    match counterclockwise {
+        false if end_angle - start_angle >= 2. * PI => 2. * PI,
+        false => (end_angle - start_angle).rem_euclid(2. * PI),
+        true if start_angle - end_angle >= 2. * PI => -2. * PI,
+        true => -(start_angle - end_angle).rem_euclid(2. * PI),
+    }
+}
+
+/// Returns the point at `angle` on an ellipse. Like the renderer, negative radii are treated as positive ones.
+fn ellipse_point(
+    center: Coordinate<f64>,
+    radius: Coordinate<f64>,
+    rotation: f64,
+    angle: f64,
+) -> Coordinate<f64> {
+    let (sin, cos) = angle.sin_cos();
+    let (rotation_sin, rotation_cos) = rotation.sin_cos();
+    let x = radius.x.abs() * cos;
+    let y = radius.y.abs() * sin;
+
+    Coordinate {
+        x: center.x + x * rotation_cos - y * rotation_sin,
+        y: center.y + x * rotation_sin + y * rotation_cos,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use geo::{prelude::EuclideanDistance, Coordinate, Point};
+
+    use super::Path;
+
+    fn c(x: f64, y: f64) -> Coordinate<f64> {
+        Coordinate { x, y }
+    }
+
+    #[test]
+    fn curves_are_flattened_within_tolerance() {
+        let center = c(100., 0.);
+        let path = Path::new()
+            .move_to(c(0., 0.))
+            .quadratic_to(c(25., 50.), c(50., 0.))
+            .arc(center, 50., PI, 0., false)
+            .move_to(c(0., 100.))
+            .cubic_to(c(0., 150.), c(100., 150.), c(100., 100.))
+            .close();
+
+        let lines = path.flatten(0.5);
+        assert_eq!(lines.0.len(), 2);
+
+        let open = &lines.0[0];
+        assert_eq!(open.0.first(), Some(&c(0., 0.)));
+        assert!(open.0.last().unwrap().euclidean_distance(&c(150., 0.)) < 1e-9);
+        assert!(open.0.len() > 10);
+
+        // The top of the parabola is close to the lines, every point of the arc lies on it
+        assert!(Point(c(25., 25.)).euclidean_distance(open) <= 0.5);
+        for point in open.0.iter().filter(|x| x.x > 50.) {
+            assert!((point.euclidean_distance(&center) - 50.).abs() < 1e-9);
+            assert!(point.y <= 1e-9);
+        }
+
+        let closed = &lines.0[1];
+        assert_eq!(closed.0.first(), closed.0.last());
+        assert!(Point(c(50., 137.5)).euclidean_distance(closed) <= 0.5);
+        assert!(path.is_closed());
+    }
+
+    #[test]
+    fn negative_radii_are_flattened_like_rendered() {
+        let arc = |radius: f64| {
+            Path::new()
+                .move_to(c(0., 0.))
+                .arc(c(100., 0.), radius, PI, 0., false)
+                .flatten(0.5)
+        };
+
+        assert_eq!(arc(-50.), arc(50.));
+    }
+}
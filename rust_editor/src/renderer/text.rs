@@ -0,0 +1,323 @@
+use geo::{Coordinate, Rect};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use super::PrimitiveRenderer;
+use crate::style::Style;
+
+/// Distance from the alphabetic baseline to the middle of the em box relative to the font size, which holds for
+/// common fonts
+static BASELINE_TO_MIDDLE: f64 = 0.3;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Font {
+    /// Accepts all values of the css `font-family` property, e.g. `"Roboto, sans-serif"`
+    pub family: String,
+
+    /// In pixels
+    pub size: f64,
+
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Font {
+            family: "sans-serif".to_string(),
+            size: 14.,
+            bold: false,
+            italic: false,
+        }
+    }
+}
+
+impl Font {
+    /// Returns the font in css format as expected by the canvas, e.g. `italic bold 14px sans-serif`.
+    pub fn css(&self) -> String {
+        let mut css = String::new();
+        if self.italic {
+            css.push_str("italic ");
+        }
+        if self.bold {
+            css.push_str("bold ");
+        }
+
+        format!("{}{}px {}", css, self.size, self.family)
+    }
+}
+
+/// Horizontal position of the text relative to its position.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    /// Part of the width of a line that lies left of the position
+    fn factor(&self) -> f64 {
+        match self {
+            TextAlign::Left => 0.,
+            TextAlign::Center => 0.5,
+            TextAlign::Right => 1.,
+        }
+    }
+}
+
+/// Vertical position of the text relative to its position. Text with several lines is placed as a block, e.g.
+/// [TextBaseline::Bottom] places the bottom of the last line at the position.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum TextBaseline {
+    Top,
+    Middle,
+
+    /// The baseline of the first line is at the position
+    #[default]
+    Alphabetic,
+
+    Bottom,
+}
+
+/// A label drawn on the canvas. Its colors are taken from the [Style] it is rendered with: the text uses
+/// `text_color`, the optional background `background_color` and `border_color`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Text {
+    /// Line breaks start a new line
+    pub content: String,
+
+    pub position: Coordinate<f64>,
+    pub font: Font,
+    pub align: TextAlign,
+    pub baseline: TextBaseline,
+
+    /// Maximal width of a line, `None` if lines can be arbitrarily wide. Lines that are still wider, e.g. a single
+    /// long word, are squeezed to fit.
+    pub max_width: Option<f64>,
+
+    /// Breaks lines wider than `max_width` between words
+    pub wrap: bool,
+
+    /// Distance between the lines relative to the font size
+    pub line_height: f64,
+
+    /// Draws a box behind the text
+    pub background: bool,
+
+    /// Space between the text and the edge of the background in pixels
+    pub padding: f64,
+}
+
+impl Text {
+    pub fn new(content: impl Into<String>, position: Coordinate<f64>) -> Self {
+        Text {
+            content: content.into(),
+            position,
+            font: Font::default(),
+            align: TextAlign::default(),
+            baseline: TextBaseline::default(),
+            max_width: None,
+            wrap: false,
+            line_height: 1.2,
+            background: false,
+            padding: 4.,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLine {
+    pub content: String,
+
+    /// Left end of the line, vertically in its middle
+    pub position: Coordinate<f64>,
+
+    pub width: f64,
+}
+
+/// Result of [measure_text].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+
+    /// Area covered by the text, including the padding if the text has a background
+    pub rect: Rect<f64>,
+}
+
+/// Breaks `text` into lines and places them, without drawing anything. Allows plugins to lay out labels, e.g. to
+/// check whether they overlap or fit into an element.
+pub fn measure_text(
+    context: &CanvasRenderingContext2d,
+    text: &Text,
+) -> Result<TextLayout, JsValue> {
+    context.save();
+    context.set_font(&text.font.css());
+    let layout = layout(text, |line| context.measure_text(line).map(|x| x.width()));
+    context.restore();
+
+    layout
+}
+
+/// Lays out `text` with `measure` returning the width of a line in the font of the text.
+fn layout<E>(
+    text: &Text,
+    mut measure: impl FnMut(&str) -> Result<f64, E>,
+) -> Result<TextLayout, E> {
+    let mut lines = Vec::new();
+    for paragraph in text.content.split('\n') {
+        let max_width = match text.wrap {
+            true => text.max_width,
+            false => None,
+        };
+
+        let mut current = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = match current.is_empty() {
+                true => word.to_string(),
+                false => format!("{} {}", current, word),
+            };
+
+            match max_width {
+                Some(max_width) if !current.is_empty() && measure(&candidate)? > max_width => {
+                    lines.push(std::mem::replace(&mut current, word.to_string()));
+                }
+                _ => current = candidate,
+            }
+        }
+        lines.push(current);
+    }
+
+    let line_height = text.font.size * text.line_height;
+    let height = line_height * lines.len() as f64;
+    let top = text.position.y
+        - match text.baseline {
+            TextBaseline::Top => 0.,
+            TextBaseline::Middle => height / 2.,
+            TextBaseline::Bottom => height,
+            TextBaseline::Alphabetic => line_height / 2. + text.font.size * BASELINE_TO_MIDDLE,
+        };
+
+    let factor = text.align.factor();
+    let mut width: f64 = 0.;
+    let mut placed = Vec::with_capacity(lines.len());
+    for (i, content) in lines.into_iter().enumerate() {
+        let mut line_width = measure(&content)?;
+        if let Some(max_width) = text.max_width {
+            line_width = line_width.min(max_width);
+        }
+        width = width.max(line_width);
+
+        placed.push(TextLine {
+            position: Coordinate {
+                x: text.position.x - line_width * factor,
+                y: top + line_height * (i as f64 + 0.5),
+            },
+            content,
+            width: line_width,
+        });
+    }
+
+    let padding = match text.background {
+        true => text.padding,
+        false => 0.,
+    };
+    let left = text.position.x - width * factor;
+
+    Ok(TextLayout {
+        lines: placed,
+        rect: Rect::new(
+            Coordinate {
+                x: left - padding,
+                y: top - padding,
+            },
+            Coordinate {
+                x: left + width + padding,
+                y: top + height + padding,
+            },
+        ),
+    })
+}
+
+impl PrimitiveRenderer for Text {
+    fn render(&self, style: &Style, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        let layout = measure_text(context, self)?;
+
+        if self.background {
+            context.begin_path();
+            layout.rect.render(style, context)?;
+        }
+
+        context.save();
+        context.set_font(&self.font.css());
+        context.set_text_align("left");
+        context.set_text_baseline("middle");
+        context.set_fill_style_str(&style.text_color);
+
+        let result = layout
+            .lines
+            .iter()
+            .try_for_each(|line| match self.max_width {
+                Some(max_width) => context.fill_text_with_max_width(
+                    &line.content,
+                    line.position.x,
+                    line.position.y,
+                    max_width,
+                ),
+                None => context.fill_text(&line.content, line.position.x, line.position.y),
+            });
+
+        context.restore();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Coordinate, Rect};
+
+    use super::{layout, Text, TextAlign, TextBaseline};
+
+    /// Every character is 10 pixels wide
+    fn measure(line: &str) -> Result<f64, ()> {
+        Ok(line.chars().count() as f64 * 10.)
+    }
+
+    #[test]
+    fn text_is_wrapped_and_aligned() {
+        let mut text = Text::new("the quick brown\nfox", Coordinate { x: 100., y: 50. });
+        text.font.size = 10.;
+        text.line_height = 2.;
+        text.max_width = Some(100.);
+        text.wrap = true;
+        text.align = TextAlign::Center;
+        text.baseline = TextBaseline::Top;
+
+        let result = layout(&text, measure).unwrap();
+        let lines: Vec<&str> = result.lines.iter().map(|x| x.content.as_str()).collect();
+        assert_eq!(lines, vec!["the quick", "brown", "fox"]);
+        assert_eq!(result.lines[1].position, Coordinate { x: 75., y: 80. });
+        assert_eq!(
+            result.rect,
+            Rect::new(
+                Coordinate { x: 55., y: 50. },
+                Coordinate { x: 145., y: 110. }
+            )
+        );
+
+        // Without wrapping, long lines are squeezed to the maximal width
+        text.wrap = false;
+        text.align = TextAlign::Right;
+        text.baseline = TextBaseline::Bottom;
+        text.background = true;
+        let result = layout(&text, measure).unwrap();
+        assert_eq!(result.lines[0].width, 100.);
+        assert_eq!(
+            result.rect,
+            Rect::new(Coordinate { x: -4., y: 6. }, Coordinate { x: 104., y: 54. })
+        );
+    }
+}
@@ -0,0 +1,420 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use geo::{
+    algorithm::line_intersection::{line_intersection, LineIntersection},
+    prelude::EuclideanDistance,
+    Coordinate, Line,
+};
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::gizmo::{draw_in_screen_space, view_scale, GetPosition};
+
+static HINT_COLOR: &str = "#F2A93B";
+
+/// Size of the marker drawn at a snapped point in screen pixels
+static HINT_SIZE: f64 = 6.0;
+
+/// Distance in pixels within which points snap by default
+static DEFAULT_TOLERANCE: f64 = 8.0;
+
+/// Kind of geometry a point snapped to. If points of several kinds are within their tolerance, the kind listed
+/// first wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SnapSource {
+    /// Position of an element
+    Point,
+
+    /// Crossing of two edges
+    Intersection,
+
+    /// Middle of an edge
+    Midpoint,
+
+    /// Closest point on an edge
+    Edge,
+
+    /// Lines of the grid
+    Grid,
+}
+
+impl SnapSource {
+    pub const ALL: [SnapSource; 5] = [
+        SnapSource::Point,
+        SnapSource::Intersection,
+        SnapSource::Midpoint,
+        SnapSource::Edge,
+        SnapSource::Grid,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapSettings {
+    pub enabled: bool,
+
+    /// Maximal distance in pixels between a point and the geometry it snaps to
+    pub tolerance: f64,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        SnapSettings {
+            enabled: true,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+/// Shows the user what a point snapped to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapHint {
+    pub source: SnapSource,
+
+    /// The snapped point
+    pub target: Coordinate<f64>,
+
+    /// The edge the point snapped to, if it snapped to an edge or its midpoint
+    pub edge: Option<Line<f64>>,
+}
+
+impl SnapHint {
+    pub fn render(&self, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        context.save();
+        context.set_stroke_style_str(HINT_COLOR);
+
+        if let Some(edge) = self.edge {
+            // One pixel wide at every zoom level
+            context.set_line_width(1. / view_scale(context));
+            context.begin_path();
+            context.move_to(edge.start.x, edge.start.y);
+            context.line_to(edge.end.x, edge.end.y);
+            context.stroke();
+        }
+
+        let result = draw_in_screen_space(context, self.target, || {
+            context.set_line_width(1.);
+            context.begin_path();
+            match self.source {
+                SnapSource::Point => {
+                    context.rect(-HINT_SIZE, -HINT_SIZE, HINT_SIZE * 2., HINT_SIZE * 2.)
+                }
+                SnapSource::Intersection => {
+                    context.move_to(-HINT_SIZE, -HINT_SIZE);
+                    context.line_to(HINT_SIZE, HINT_SIZE);
+                    context.move_to(-HINT_SIZE, HINT_SIZE);
+                    context.line_to(HINT_SIZE, -HINT_SIZE);
+                }
+                SnapSource::Midpoint => {
+                    context.move_to(0., -HINT_SIZE);
+                    context.line_to(HINT_SIZE, HINT_SIZE);
+                    context.line_to(-HINT_SIZE, HINT_SIZE);
+                    context.close_path();
+                }
+                SnapSource::Edge | SnapSource::Grid => {
+                    context.arc(0., 0., HINT_SIZE, 0., 2. * PI)?
+                }
+            }
+            context.stroke();
+
+            Ok(())
+        });
+
+        context.restore();
+
+        result
+    }
+}
+
+/// Lines of the grid points can snap to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapGrid {
+    /// Distance between two main lines
+    pub offset: f64,
+
+    /// Number of evenly spaced lines from one main line to the next, including the main line itself
+    pub subdivisions: u32,
+}
+
+impl SnapGrid {
+    /// Returns the grid line nearest to `value` on one axis. Snaps to the nearest multiple of the offset first
+    /// and then to the nearest subdivision inside it, which are the lines the grid draws.
+    fn nearest_line(&self, value: f64) -> f64 {
+        let cell = (value / self.offset).floor() * self.offset;
+        let subdivision = self.offset / self.subdivisions.max(1) as f64;
+
+        cell + ((value - cell) / subdivision).round() * subdivision
+    }
+}
+
+/// Result of [Snapping::snap].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapped {
+    pub point: Coordinate<f64>,
+
+    /// What the point snapped to, `None` if it was returned unchanged
+    pub hint: Option<SnapHint>,
+}
+
+/// Moves points onto nearby geometry while they are dragged.
+///
+/// The editor holds one instance that is shared by gizmos and tools, see [App::snapping](crate::ui::app::App::snapping).
+/// Whoever starts a drag sets the geometry that can be snapped to, which should leave out the dragged elements
+/// themselves. The grid spacing is set by the grid plugin.
+#[derive(Clone, Debug)]
+pub struct Snapping {
+    enabled: bool,
+    settings: HashMap<SnapSource, SnapSettings>,
+
+    /// Lines of the grid, `None` if there is no grid
+    grid: Option<SnapGrid>,
+
+    points: Vec<Coordinate<f64>>,
+    edges: Vec<Line<f64>>,
+    intersections: Vec<Coordinate<f64>>,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Snapping {
+            enabled: true,
+            settings: SnapSource::ALL
+                .into_iter()
+                .map(|source| (source, SnapSettings::default()))
+                .collect(),
+            grid: None,
+            points: Vec::new(),
+            edges: Vec::new(),
+            intersections: Vec::new(),
+        }
+    }
+}
+
+impl Snapping {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns snapping on or off as a whole, keeping the settings of each source.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn settings(&self, source: SnapSource) -> SnapSettings {
+        self.settings.get(&source).copied().unwrap_or_default()
+    }
+
+    pub fn set_settings(&mut self, source: SnapSource, settings: SnapSettings) {
+        self.settings.insert(source, settings);
+    }
+
+    pub fn set_source_enabled(&mut self, source: SnapSource, enabled: bool) {
+        self.settings.entry(source).or_default().enabled = enabled;
+    }
+
+    pub fn set_tolerance(&mut self, source: SnapSource, tolerance: f64) {
+        self.settings.entry(source).or_default().tolerance = tolerance;
+    }
+
+    pub fn grid(&self) -> Option<SnapGrid> {
+        self.grid
+    }
+
+    /// Sets the lines of the grid. A grid of `None` or with an offset of zero disables snapping to the grid.
+    pub fn set_grid(&mut self, grid: Option<SnapGrid>) {
+        self.grid = grid.filter(|grid| grid.offset > 0.);
+    }
+
+    /// Sets the points that can be snapped to, replacing the previous ones.
+    pub fn set_points(&mut self, points: impl IntoIterator<Item = Coordinate<f64>>) {
+        self.points = points.into_iter().collect();
+    }
+
+    /// Sets the positions of `elements` as the points that can be snapped to.
+    pub fn set_elements<'a, T: GetPosition + 'a>(&mut self, elements: impl Iterator<Item = &'a T>) {
+        self.set_points(elements.map(|x| x.position()));
+    }
+
+    /// Sets the edges that can be snapped to, together with their midpoints and intersections.
+    pub fn set_edges(&mut self, edges: impl IntoIterator<Item = Line<f64>>) {
+        self.edges = edges.into_iter().collect();
+
+        self.intersections.clear();
+        for (i, a) in self.edges.iter().enumerate() {
+            for b in &self.edges[i + 1..] {
+                if let Some(LineIntersection::SinglePoint { intersection, .. }) =
+                    line_intersection(*a, *b)
+                {
+                    self.intersections.push(intersection);
+                }
+            }
+        }
+    }
+
+    /// Removes all points and edges, e.g. once a drag ended.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.edges.clear();
+        self.intersections.clear();
+    }
+
+    /// Returns `point` moved onto the nearest geometry within the tolerance of its source.
+    pub fn snap(&self, point: Coordinate<f64>) -> Snapped {
+        self.snap_at_scale(point, 1.)
+    }
+
+    /// Like [snap](Snapping::snap), but measures the tolerances in screen pixels at the given zoom of the camera, see
+    /// [view_scale](crate::gizmo::view_scale).
+    pub fn snap_at_scale(&self, point: Coordinate<f64>, view_scale: f64) -> Snapped {
+        let snapped = match self.enabled {
+            true => SnapSource::ALL
+                .into_iter()
+                .filter(|source| self.settings(*source).enabled)
+                .find_map(|source| self.snap_to(source, point, view_scale)),
+            false => None,
+        };
+
+        match snapped {
+            Some(hint) => Snapped {
+                point: hint.target,
+                hint: Some(hint),
+            },
+            None => Snapped { point, hint: None },
+        }
+    }
+
+    fn snap_to(
+        &self,
+        source: SnapSource,
+        point: Coordinate<f64>,
+        view_scale: f64,
+    ) -> Option<SnapHint> {
+        let tolerance = self.settings(source).tolerance / view_scale;
+        let hint = |target: Coordinate<f64>, edge: Option<Line<f64>>| SnapHint {
+            source,
+            target,
+            edge,
+        };
+
+        let candidates: Vec<SnapHint> = match source {
+            SnapSource::Point => self.points.iter().map(|p| hint(*p, None)).collect(),
+            SnapSource::Intersection => self.intersections.iter().map(|p| hint(*p, None)).collect(),
+            SnapSource::Midpoint => self
+                .edges
+                .iter()
+                .map(|edge| hint((edge.start + edge.end) / 2., Some(*edge)))
+                .collect(),
+            SnapSource::Edge => self
+                .edges
+                .iter()
+                .map(|edge| hint(closest_point(edge, point), Some(*edge)))
+                .collect(),
+            SnapSource::Grid => {
+                let grid = self.grid?;
+                let snap_axis = |value: f64| {
+                    let line = grid.nearest_line(value);
+                    match (line - value).abs() <= tolerance {
+                        true => line,
+                        false => value,
+                    }
+                };
+
+                let target = Coordinate {
+                    x: snap_axis(point.x),
+                    y: snap_axis(point.y),
+                };
+                match target == point {
+                    true => Vec::new(),
+                    false => vec![hint(target, None)],
+                }
+            }
+        };
+
+        // Each axis snaps to the grid on its own, so a grid crossing is within reach if both axes are
+        let distance = |target: Coordinate<f64>| match source {
+            SnapSource::Grid => (target.x - point.x).abs().max((target.y - point.y).abs()),
+            _ => target.euclidean_distance(&point),
+        };
+
+        candidates
+            .into_iter()
+            .map(|hint| (distance(hint.target), hint))
+            .filter(|(distance, _)| *distance <= tolerance)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, hint)| hint)
+    }
+}
+
+/// Returns the point on `line` that is closest to `point`.
+fn closest_point(line: &Line<f64>, point: Coordinate<f64>) -> Coordinate<f64> {
+    let direction = line.delta();
+    let length = direction.x * direction.x + direction.y * direction.y;
+    if length == 0. {
+        return line.start;
+    }
+
+    let diff = point - line.start;
+    let t = ((diff.x * direction.x + diff.y * direction.y) / length).clamp(0., 1.);
+
+    line.start + direction * t
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Coordinate, Line};
+
+    use super::{SnapGrid, SnapSource, Snapping};
+
+    fn c(x: f64, y: f64) -> Coordinate<f64> {
+        Coordinate { x, y }
+    }
+
+    fn source(snapping: &Snapping, point: Coordinate<f64>) -> Option<SnapSource> {
+        snapping.snap(point).hint.map(|hint| hint.source)
+    }
+
+    #[test]
+    fn points_snap_to_nearby_geometry() {
+        let mut snapping = Snapping::default();
+        snapping.set_grid(Some(SnapGrid {
+            offset: 100.,
+            subdivisions: 2,
+        }));
+        snapping.set_points(vec![c(203., 203.)]);
+        snapping.set_edges(vec![
+            Line::new(c(0., 0.), c(100., 0.)),
+            Line::new(c(20., -50.), c(20., 30.)),
+        ]);
+
+        assert_eq!(snapping.snap(c(198., 205.)).point, c(203., 203.));
+        assert_eq!(
+            source(&snapping, c(22., 3.)),
+            Some(SnapSource::Intersection)
+        );
+        assert_eq!(snapping.snap(c(52., 4.)).point, c(50., 0.));
+        assert_eq!(source(&snapping, c(52., 4.)), Some(SnapSource::Midpoint));
+        assert_eq!(snapping.snap(c(80., 3.)).point, c(80., 0.));
+        assert_eq!(snapping.snap(c(148., 120.)).point, c(150., 120.));
+        assert_eq!(snapping.snap(c(125., 125.)).hint, None);
+
+        snapping.set_source_enabled(SnapSource::Intersection, false);
+        assert_eq!(source(&snapping, c(22., 3.)), Some(SnapSource::Edge));
+
+        snapping.set_enabled(false);
+        assert_eq!(snapping.snap(c(198., 205.)).point, c(198., 205.));
+    }
+
+    #[test]
+    fn grid_snaps_to_drawn_subdivisions() {
+        let mut snapping = Snapping::default();
+        snapping.set_grid(Some(SnapGrid {
+            offset: 150.,
+            subdivisions: 4,
+        }));
+
+        // Subdivisions of the second cell lie at 150, 187.5, 225 and 262.5
+        assert_eq!(snapping.snap(c(190., 226.)).point, c(187.5, 225.));
+        assert_eq!(snapping.snap(c(298., -2.)).point, c(300., 0.));
+        assert_eq!(snapping.snap(c(193., 231.)).point, c(187.5, 225.));
+        assert_eq!(snapping.snap(c(206., 206.)).hint, None);
+    }
+}
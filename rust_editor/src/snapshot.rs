@@ -0,0 +1,218 @@
+use std::{cell::RefCell, fmt, rc::Rc, sync::Arc};
+
+use crate::actions::{Action, Redo, Undo};
+
+/// Records all changes made to the data between [begin](Recorder::begin) and [finish](Recorder::finish) and turns them into
+/// a single undoable action. This allows to modify the data directly instead of implementing [Undo] and [Redo] for each
+/// mutation, see [App::set_recorder](crate::ui::app::App::set_recorder).
+pub trait Recorder<Data> {
+    /// Called before the data is modified.
+    fn begin(&mut self, data: &Data);
+
+    /// Called after the data was modified. Returns an action that restores the data as it was when [begin](Recorder::begin)
+    /// was called or `None` if nothing changed.
+    fn finish(&mut self, data: &Data) -> Option<Rc<RefCell<dyn Action<Data>>>>;
+}
+
+/// Computes the changes between two states of the data.
+pub trait Diff {
+    type Patch: Send + Sync;
+
+    /// Returns the patch that turns `self` into `other` or `None` if both are equal.
+    fn diff(&self, other: &Self) -> Option<Self::Patch>;
+
+    fn apply(&mut self, patch: &Self::Patch);
+}
+
+/// Restores complete copies of the data.
+pub struct SnapshotAction<Data> {
+    before: Arc<Data>,
+    after: Arc<Data>,
+}
+
+impl<Data> fmt::Display for SnapshotAction<Data> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Edit")
+    }
+}
+
+impl<Data: Clone + Send + Sync> Undo<Data> for SnapshotAction<Data> {
+    fn undo(&mut self, data: &mut Data) {
+        *data = (*self.before).clone();
+    }
+}
+
+impl<Data: Clone + Send + Sync> Redo<Data> for SnapshotAction<Data> {
+    fn redo(&mut self, data: &mut Data) {
+        *data = (*self.after).clone();
+    }
+}
+
+impl<Data: Clone + Send + Sync> Action<Data> for SnapshotAction<Data> {}
+
+/// Records changes by keeping a copy of the data before and after each step.
+///
+/// Consecutive steps share the copy in between, so each state is only kept once. Cloning large documents stays cheap if
+/// the data itself shares its content, e.g. by holding it in [Arc]s.
+///
+/// Each step that changes the data clones it once and compares it with the previous state, which takes time
+/// proportional to the size of the data. Steps that do not access the data mutably are skipped by the editor. Prefer
+/// the [DiffRecorder] for large documents that do not share their content.
+pub struct SnapshotRecorder<Data> {
+    before: Option<Arc<Data>>,
+
+    /// State after the most recent recorded step
+    latest: Option<Arc<Data>>,
+}
+
+impl<Data> Default for SnapshotRecorder<Data> {
+    fn default() -> Self {
+        SnapshotRecorder {
+            before: None,
+            latest: None,
+        }
+    }
+}
+
+impl<Data> Recorder<Data> for SnapshotRecorder<Data>
+where
+    Data: Clone + PartialEq + Send + Sync + 'static,
+{
+    fn begin(&mut self, data: &Data) {
+        self.before = match &self.latest {
+            Some(latest) if **latest == *data => Some(Arc::clone(latest)),
+            _ => Some(Arc::new(data.clone())),
+        };
+    }
+
+    fn finish(&mut self, data: &Data) -> Option<Rc<RefCell<dyn Action<Data>>>> {
+        let before = self.before.take()?;
+        if *before == *data {
+            return None;
+        }
+
+        let after = Arc::new(data.clone());
+        self.latest = Some(Arc::clone(&after));
+
+        Some(Rc::new(RefCell::new(SnapshotAction { before, after })))
+    }
+}
+
+/// Applies the patches between two states of the data.
+pub struct DiffAction<Data: Diff> {
+    forward: Data::Patch,
+    backward: Data::Patch,
+}
+
+impl<Data: Diff> fmt::Display for DiffAction<Data> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Edit")
+    }
+}
+
+impl<Data: Diff> Undo<Data> for DiffAction<Data> {
+    fn undo(&mut self, data: &mut Data) {
+        data.apply(&self.backward);
+    }
+}
+
+impl<Data: Diff> Redo<Data> for DiffAction<Data> {
+    fn redo(&mut self, data: &mut Data) {
+        data.apply(&self.forward);
+    }
+}
+
+impl<Data: Diff> Action<Data> for DiffAction<Data> {}
+
+/// Records changes as patches between the data before and after each step. Only the patches are kept, which is usually
+/// much less than a copy of the data.
+pub struct DiffRecorder<Data> {
+    before: Option<Data>,
+}
+
+impl<Data> Default for DiffRecorder<Data> {
+    fn default() -> Self {
+        DiffRecorder { before: None }
+    }
+}
+
+impl<Data> Recorder<Data> for DiffRecorder<Data>
+where
+    Data: Clone + Diff + 'static,
+{
+    fn begin(&mut self, data: &Data) {
+        self.before = Some(data.clone());
+    }
+
+    fn finish(&mut self, data: &Data) -> Option<Rc<RefCell<dyn Action<Data>>>> {
+        let before = self.before.take()?;
+        let forward = before.diff(data)?;
+        let backward = data.diff(&before)?;
+
+        Some(Rc::new(RefCell::new(DiffAction::<Data> {
+            forward,
+            backward,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{Diff, DiffRecorder, Recorder, SnapshotRecorder};
+
+    #[derive(Clone, PartialEq)]
+    struct Counter(i32);
+
+    impl Diff for Counter {
+        type Patch = i32;
+
+        fn diff(&self, other: &Self) -> Option<i32> {
+            match other.0 - self.0 {
+                0 => None,
+                delta => Some(delta),
+            }
+        }
+
+        fn apply(&mut self, patch: &i32) {
+            self.0 += patch;
+        }
+    }
+
+    fn record<R: Recorder<Counter>>(recorder: &mut R, data: &mut Counter, value: i32) {
+        recorder.begin(data);
+        data.0 = value;
+
+        let action = recorder.finish(data).unwrap();
+        action.borrow_mut().undo(data);
+        assert_eq!(data.0, 1);
+
+        action.borrow_mut().redo(data);
+        assert_eq!(data.0, value);
+    }
+
+    #[test]
+    fn snapshot_recorder() {
+        let mut recorder = SnapshotRecorder::default();
+        let mut data = Counter(1);
+        record(&mut recorder, &mut data, 5);
+
+        recorder.begin(&data);
+        assert!(Arc::ptr_eq(
+            recorder.before.as_ref().unwrap(),
+            recorder.latest.as_ref().unwrap()
+        ));
+        assert!(recorder.finish(&data).is_none());
+    }
+
+    #[test]
+    fn diff_recorder() {
+        let mut recorder = DiffRecorder::default();
+        let mut data = Counter(1);
+        record(&mut recorder, &mut data, 5);
+
+        recorder.begin(&data);
+        assert!(recorder.finish(&data).is_none());
+    }
+}
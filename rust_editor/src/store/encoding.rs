@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::StoreError;
+
+/// Marks values that are not saved as plain json. It starts with a zero byte, which never starts a json document.
+const MAGIC: &[u8; 4] = b"\0RED";
+
+/// Version of the header layout
+const HEADER_VERSION: u8 = 1;
+
+const HEADER_LENGTH: usize = MAGIC.len() + 3;
+
+/// Level used for deflate compression, balances speed and size
+const DEFLATE_LEVEL: u8 = 6;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Human readable, but slow and large for big documents
+    #[default]
+    Json,
+
+    /// Compact binary encoding that keeps field names, so older documents can still be migrated
+    MessagePack,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Deflate,
+}
+
+/// Describes how a [Store](super::Store) saves its value.
+///
+/// Values that are not uncompressed json start with a header naming their encoding and compression, so a store can
+/// always read values saved in another format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Format {
+    pub encoding: Encoding,
+    pub compression: Compression,
+}
+
+impl Encoding {
+    fn id(&self) -> u8 {
+        match self {
+            Encoding::Json => 0,
+            Encoding::MessagePack => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, StoreError> {
+        match id {
+            0 => Ok(Encoding::Json),
+            1 => Ok(Encoding::MessagePack),
+            _ => Err(StoreError::UnknownFormat(format!("encoding {}", id))),
+        }
+    }
+
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, StoreError> {
+        match self {
+            Encoding::Json => {
+                serde_json::to_vec(value).map_err(|e| StoreError::Serialization(Box::new(e)))
+            }
+            // Maps are used for structs since migrations need the field names
+            Encoding::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| StoreError::Serialization(Box::new(e)))
+            }
+        }
+    }
+
+    /// Deserializes `bytes` and reports the path of the first value that does not match `T`.
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, StoreError> {
+        fn error<E>(e: serde_path_to_error::Error<E>) -> StoreError
+        where
+            E: std::error::Error + Send + Sync + 'static,
+        {
+            StoreError::Deserialization {
+                path: e.path().to_string(),
+                source: Box::new(e.into_inner()),
+            }
+        }
+
+        match self {
+            Encoding::Json => {
+                let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+                let value = serde_path_to_error::deserialize(&mut deserializer).map_err(error)?;
+                deserializer
+                    .end()
+                    .map_err(|source| StoreError::Deserialization {
+                        path: String::from("."),
+                        source: Box::new(source),
+                    })?;
+
+                Ok(value)
+            }
+            Encoding::MessagePack => {
+                let mut deserializer = rmp_serde::Deserializer::from_read_ref(bytes);
+                serde_path_to_error::deserialize(&mut deserializer).map_err(error)
+            }
+        }
+    }
+}
+
+impl Compression {
+    fn id(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, StoreError> {
+        match id {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Deflate),
+            _ => Err(StoreError::UnknownFormat(format!("compression {}", id))),
+        }
+    }
+}
+
+impl Format {
+    pub fn new(encoding: Encoding, compression: Compression) -> Self {
+        Format {
+            encoding,
+            compression,
+        }
+    }
+
+    /// Encodes `value` in this format, including the header if one is needed.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, StoreError> {
+        let payload = self.encoding.serialize(value)?;
+
+        if *self == Format::default() {
+            return Ok(payload);
+        }
+
+        let payload = match self.compression {
+            Compression::None => payload,
+            Compression::Deflate => miniz_oxide::deflate::compress_to_vec(&payload, DEFLATE_LEVEL),
+        };
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[HEADER_VERSION, self.encoding.id(), self.compression.id()]);
+        bytes.extend_from_slice(&payload);
+
+        Ok(bytes)
+    }
+
+    /// Detects the format of `bytes` from their header and returns it together with the decompressed payload.
+    pub fn decode(bytes: &[u8]) -> Result<(Format, Cow<'_, [u8]>), StoreError> {
+        if !bytes.starts_with(MAGIC) {
+            return Ok((Format::default(), Cow::Borrowed(bytes)));
+        }
+
+        if bytes.len() < HEADER_LENGTH {
+            return Err(StoreError::UnknownFormat(String::from("truncated header")));
+        }
+
+        let header = &bytes[MAGIC.len()..HEADER_LENGTH];
+        if header[0] != HEADER_VERSION {
+            return Err(StoreError::UnknownFormat(format!(
+                "header version {}",
+                header[0]
+            )));
+        }
+
+        let format = Format::new(
+            Encoding::from_id(header[1])?,
+            Compression::from_id(header[2])?,
+        );
+        let payload = &bytes[HEADER_LENGTH..];
+
+        let payload = match format.compression {
+            Compression::None => Cow::Borrowed(payload),
+            Compression::Deflate => Cow::Owned(
+                miniz_oxide::inflate::decompress_to_vec(payload).map_err(|e| {
+                    StoreError::UnknownFormat(format!("corrupted deflate stream: {:?}", e))
+                })?,
+            ),
+        };
+
+        Ok((format, payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Compression, Encoding, Format};
+
+    #[test]
+    fn formats_are_detected() {
+        let value = json!({ "points": [[1.5, 2.0], [3.0, 4.25]], "name": "map" });
+
+        for encoding in [Encoding::Json, Encoding::MessagePack] {
+            for compression in [Compression::None, Compression::Deflate] {
+                let format = Format::new(encoding, compression);
+                let bytes = format.encode(&value).unwrap();
+
+                let (detected, payload) = Format::decode(&bytes).unwrap();
+                assert_eq!(detected, format);
+                assert_eq!(
+                    detected
+                        .encoding
+                        .deserialize::<serde_json::Value>(&payload)
+                        .unwrap(),
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plain_json_has_no_header() {
+        let bytes = Format::default().encode(&json!([1, 2])).unwrap();
+        assert_eq!(bytes, b"[1,2]");
+    }
+}
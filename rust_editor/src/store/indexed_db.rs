@@ -0,0 +1,255 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Array, Function, Promise, Uint8Array};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+use super::{StorageBackend, StorageFuture, StoreError};
+
+/// Saves values to an object store of an IndexedDB database. The browser grants IndexedDB a much larger quota than the
+/// local storage, so it is the better choice for large documents.
+///
+/// The database is opened on first use and created if it does not exist yet. Several backends can share a database with
+/// different object stores, e.g. one for autosaving and one for a library. A missing object store is added by upgrading
+/// the database to the next version.
+pub struct IndexedDbBackend {
+    database_name: String,
+    store_name: String,
+
+    connection: Rc<RefCell<Option<Connection>>>,
+}
+
+struct Connection {
+    database: IdbDatabase,
+
+    /// Closes the connection when another backend upgrades the database
+    _on_version_change: Closure<dyn FnMut(JsValue)>,
+}
+
+thread_local! {
+    /// Resolves once the most recently started open finished
+    static OPENING: RefCell<Option<Promise>> = RefCell::new(None);
+}
+
+/// Makes backends open their databases one after another. Released when dropped.
+struct OpenLock {
+    release: Function,
+}
+
+impl OpenLock {
+    async fn acquire() -> Self {
+        let mut release = None;
+        let done = Promise::new(&mut |resolve, _| release = Some(resolve));
+
+        if let Some(previous) = OPENING.with(|opening| opening.replace(Some(done))) {
+            // The previous open failing does not matter
+            let _ = JsFuture::from(previous).await;
+        }
+
+        OpenLock {
+            release: release.unwrap(),
+        }
+    }
+}
+
+impl Drop for OpenLock {
+    fn drop(&mut self) {
+        let _ = self.release.call0(&JsValue::NULL);
+    }
+}
+
+/// Waits until `request` succeeded and returns its result.
+async fn request_result(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+
+    let result = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+
+    match result {
+        Ok(_) => request.result(),
+        Err(e) => Err(request.error()?.map(JsValue::from).unwrap_or(e)),
+    }
+}
+
+/// Waits until all changes of `transaction` are written.
+async fn transaction_complete(transaction: &IdbTransaction) -> Result<(), JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+
+    let result = JsFuture::from(promise).await;
+    transaction.set_oncomplete(None);
+    transaction.set_onerror(None);
+    transaction.set_onabort(None);
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(transaction.error().map(JsValue::from).unwrap_or(e)),
+    }
+}
+
+impl IndexedDbBackend {
+    /// Creates a backend that saves to the object store `store_name` of the database `database_name`.
+    pub fn new(database_name: &str, store_name: &str) -> Self {
+        IndexedDbBackend {
+            database_name: String::from(database_name),
+            store_name: String::from(store_name),
+            connection: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    async fn database(&self) -> Result<IdbDatabase, StoreError> {
+        if let Some(connection) = self.connection.borrow().as_ref() {
+            return Ok(connection.database.clone());
+        }
+
+        // Opening may upgrade the database, which must not interleave with another backend doing the same
+        let _lock = OpenLock::acquire().await;
+        if let Some(connection) = self.connection.borrow().as_ref() {
+            return Ok(connection.database.clone());
+        }
+
+        let unavailable = |reason: &str| StoreError::Unavailable {
+            reason: String::from(reason),
+        };
+
+        let factory = web_sys::window()
+            .ok_or_else(|| unavailable("there is no window"))?
+            .indexed_db()
+            .ok()
+            .flatten()
+            .ok_or_else(|| unavailable("IndexedDB is disabled"))?;
+
+        let request = factory.open(&self.database_name)?;
+        let mut database = request_result(&request)
+            .await?
+            .unchecked_into::<IdbDatabase>();
+
+        // Other backends may use other object stores of the same database, so a missing store is added by upgrading to
+        // the next version
+        if !database.object_store_names().contains(&self.store_name) {
+            let version = database.version() + 1.;
+            database.close();
+
+            let request = factory.open_with_f64(&self.database_name, version)?;
+            let store_name = self.store_name.clone();
+            let on_upgrade_needed = {
+                let request = request.clone();
+
+                Closure::wrap(Box::new(move |_: JsValue| {
+                    let database = match request.result() {
+                        Ok(database) => database.unchecked_into::<IdbDatabase>(),
+                        Err(_) => return,
+                    };
+
+                    if !database.object_store_names().contains(&store_name) {
+                        if let Err(e) = database.create_object_store(&store_name) {
+                            crate::error!("could not create object store: {:?}", e);
+                        }
+                    }
+                }) as Box<dyn FnMut(JsValue)>)
+            };
+            request.set_onupgradeneeded(Some(on_upgrade_needed.as_ref().unchecked_ref()));
+
+            let result = request_result(&request).await;
+            request.set_onupgradeneeded(None);
+
+            database = result?.unchecked_into::<IdbDatabase>();
+        }
+
+        // Connections of other backends are closed when the database is upgraded, otherwise the upgrade is blocked. The
+        // database is opened again on the next access.
+        let on_version_change = {
+            let database = database.clone();
+            let connection = Rc::downgrade(&self.connection);
+
+            Closure::wrap(Box::new(move |_: JsValue| {
+                database.close();
+                if let Some(connection) = connection.upgrade() {
+                    connection.borrow_mut().take();
+                }
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        database.set_onversionchange(Some(on_version_change.as_ref().unchecked_ref()));
+
+        *self.connection.borrow_mut() = Some(Connection {
+            database: database.clone(),
+            _on_version_change: on_version_change,
+        });
+
+        Ok(database)
+    }
+
+    async fn transaction(&self, mode: IdbTransactionMode) -> Result<IdbTransaction, StoreError> {
+        Ok(self
+            .database()
+            .await?
+            .transaction_with_str_and_mode(&self.store_name, mode)?)
+    }
+}
+
+impl StorageBackend for IndexedDbBackend {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let transaction = self.transaction(IdbTransactionMode::Readonly).await?;
+            let request = transaction
+                .object_store(&self.store_name)?
+                .get(&JsValue::from_str(key))?;
+
+            let value = request_result(&request).await?;
+
+            // Values saved before binary values were supported are strings
+            if let Some(value) = value.as_string() {
+                return Ok(Some(value.into_bytes()));
+            }
+
+            match value.dyn_into::<Uint8Array>() {
+                Ok(value) => Ok(Some(value.to_vec())),
+                Err(_) => Ok(None),
+            }
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let transaction = self.transaction(IdbTransactionMode::Readwrite).await?;
+            transaction
+                .object_store(&self.store_name)?
+                .put_with_key(&Uint8Array::from(value.as_slice()), &JsValue::from_str(key))?;
+
+            Ok(transaction_complete(&transaction).await?)
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let transaction = self.transaction(IdbTransactionMode::Readwrite).await?;
+            transaction
+                .object_store(&self.store_name)?
+                .delete(&JsValue::from_str(key))?;
+
+            Ok(transaction_complete(&transaction).await?)
+        })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let transaction = self.transaction(IdbTransactionMode::Readonly).await?;
+            let request = transaction.object_store(&self.store_name)?.get_all_keys()?;
+
+            let keys = request_result(&request).await?;
+            Ok(keys
+                .unchecked_into::<Array>()
+                .iter()
+                .filter_map(|key| key.as_string())
+                .collect())
+        })
+    }
+}
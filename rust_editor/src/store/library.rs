@@ -0,0 +1,371 @@
+use std::rc::Rc;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use super::{Format, MigrationRegistry, StorageBackend, Store, StoreError};
+
+/// Describes a document saved in a [Library].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DocumentInfo {
+    pub id: Uuid,
+    pub title: String,
+
+    /// Milliseconds since the unix epoch
+    pub created: f64,
+
+    /// Milliseconds since the unix epoch
+    pub modified: f64,
+
+    /// Milliseconds since the unix epoch, `None` if the document was never opened
+    #[serde(default)]
+    pub opened: Option<f64>,
+
+    /// Preview of the document as data url, see [App::thumbnail](crate::ui::app::App::thumbnail)
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct LibraryIndex {
+    documents: Vec<DocumentInfo>,
+}
+
+/// Saves any number of named documents in a [StorageBackend].
+///
+/// The metadata of all documents is kept in an index, so listing the documents does not need to load them. Every
+/// document is saved with its own [Store], which uses the format and migrations of the library.
+///
+/// # Example
+///
+/// ```
+/// let library = Library::new("maps", Rc::new(IndexedDbBackend::new("editor", "documents")));
+///
+/// let info = library.create("Berlin", editor.data(), editor.thumbnail(160, 90)).await?;
+/// library.rename(info.id, "Berlin Mitte").await?;
+///
+/// for document in library.recent(5).await? {
+///     log!("{}", document.title);
+/// }
+///
+/// let map: Map = library.open(info.id).await?;
+/// ```
+pub struct Library {
+    backend: Rc<dyn StorageBackend>,
+    name: String,
+    migrations: Rc<MigrationRegistry>,
+    format: Format,
+}
+
+impl Library {
+    /// Creates a library whose documents are saved under keys starting with `name`.
+    pub fn new(name: &str, backend: Rc<dyn StorageBackend>) -> Self {
+        Library {
+            backend,
+            name: String::from(name),
+            migrations: Rc::default(),
+            format: Format::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the migrations used to load documents, see [Store::set_migrations].
+    pub fn set_migrations(&mut self, migrations: Rc<MigrationRegistry>) {
+        self.migrations = migrations;
+    }
+
+    /// Sets the format documents are saved in, see [Store::set_format].
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    pub fn backend(&self) -> Rc<dyn StorageBackend> {
+        Rc::clone(&self.backend)
+    }
+
+    /// Returns the key the document `id` is saved under. Plugins that save additional data with a document, e.g. its
+    /// undo history, can derive their keys from it.
+    pub fn document_name(&self, id: Uuid) -> String {
+        format!("{}.document.{}", self.name, id)
+    }
+
+    fn index_store(&self) -> Store {
+        Store::with_backend(&format!("{}.library", self.name), Rc::clone(&self.backend))
+    }
+
+    fn document_store(&self, id: Uuid) -> Store {
+        let mut store = Store::with_backend(&self.document_name(id), Rc::clone(&self.backend));
+        store.set_migrations(Rc::clone(&self.migrations));
+        store.set_format(self.format);
+
+        store
+    }
+
+    async fn index(&self) -> Result<LibraryIndex, StoreError> {
+        Ok(self.index_store().fetch().await?.unwrap_or_default())
+    }
+
+    /// Applies `update` to the metadata of the document `id` and saves the index.
+    async fn update(
+        &self,
+        id: Uuid,
+        update: impl FnOnce(&mut DocumentInfo),
+    ) -> Result<DocumentInfo, StoreError> {
+        let mut index = self.index().await?;
+        let info = index
+            .documents
+            .iter_mut()
+            .find(|info| info.id == id)
+            .ok_or(StoreError::DocumentNotFound(id))?;
+
+        update(info);
+        let info = info.clone();
+
+        self.index_store().sync(&index).await?;
+
+        Ok(info)
+    }
+
+    /// Returns all documents, the most recently modified one first.
+    pub async fn list(&self) -> Result<Vec<DocumentInfo>, StoreError> {
+        let mut documents = self.index().await?.documents;
+        documents.sort_by(|a, b| b.modified.total_cmp(&a.modified));
+
+        Ok(documents)
+    }
+
+    /// Returns up to `count` documents that were opened before, the most recently opened one first.
+    pub async fn recent(&self, count: usize) -> Result<Vec<DocumentInfo>, StoreError> {
+        let mut documents: Vec<DocumentInfo> = self
+            .index()
+            .await?
+            .documents
+            .into_iter()
+            .filter(|info| info.opened.is_some())
+            .collect();
+        documents.sort_by(|a, b| b.opened.unwrap_or(0.).total_cmp(&a.opened.unwrap_or(0.)));
+        documents.truncate(count);
+
+        Ok(documents)
+    }
+
+    /// Saves `data` as a new document.
+    pub async fn create<T: Serialize>(
+        &self,
+        title: &str,
+        data: &T,
+        thumbnail: Option<String>,
+    ) -> Result<DocumentInfo, StoreError> {
+        let now = now();
+        let info = DocumentInfo {
+            id: Uuid::new_v4(),
+            title: String::from(title),
+            created: now,
+            modified: now,
+            opened: None,
+            thumbnail,
+        };
+
+        // The document is written before the index, so the index never lists a document that does not exist
+        self.document_store(info.id)
+            .sync_document(data, metadata(&info))
+            .await?;
+
+        let mut index = self.index().await?;
+        index.documents.push(info.clone());
+        self.index_store().sync(&index).await?;
+
+        Ok(info)
+    }
+
+    /// Loads the document `id` and marks it as recently opened.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [StoreError::DocumentNotFound] if there is no such document, or if loading the document fails, see
+    /// [Store::fetch].
+    pub async fn open<T: DeserializeOwned>(&self, id: Uuid) -> Result<T, StoreError> {
+        let data = self
+            .document_store(id)
+            .fetch()
+            .await?
+            .ok_or(StoreError::DocumentNotFound(id))?;
+
+        self.update(id, |info| info.opened = Some(now())).await?;
+
+        Ok(data)
+    }
+
+    /// Replaces the data of the document `id`. The thumbnail is kept if `thumbnail` is `None`.
+    pub async fn save<T: Serialize>(
+        &self,
+        id: Uuid,
+        data: &T,
+        thumbnail: Option<String>,
+    ) -> Result<DocumentInfo, StoreError> {
+        let mut index = self.index().await?;
+        let info = index
+            .documents
+            .iter_mut()
+            .find(|info| info.id == id)
+            .ok_or(StoreError::DocumentNotFound(id))?;
+
+        info.modified = now();
+        if thumbnail.is_some() {
+            info.thumbnail = thumbnail;
+        }
+        let info = info.clone();
+
+        // The document is written before the index, so the index never describes data that was not saved
+        self.document_store(id)
+            .sync_document(data, metadata(&info))
+            .await?;
+        self.index_store().sync(&index).await?;
+
+        Ok(info)
+    }
+
+    pub async fn rename(&self, id: Uuid, title: &str) -> Result<DocumentInfo, StoreError> {
+        self.update(id, |info| info.title = String::from(title))
+            .await
+    }
+
+    /// Copies the document `id` into a new document named `title`.
+    pub async fn duplicate(&self, id: Uuid, title: &str) -> Result<DocumentInfo, StoreError> {
+        let mut index = self.index().await?;
+        let source = index
+            .documents
+            .iter()
+            .find(|info| info.id == id)
+            .ok_or(StoreError::DocumentNotFound(id))?;
+
+        let now = now();
+        let info = DocumentInfo {
+            id: Uuid::new_v4(),
+            title: String::from(title),
+            created: now,
+            modified: now,
+            opened: None,
+            thumbnail: source.thumbnail.clone(),
+        };
+
+        // The data is copied as json, so the type of the document does not need to be known. The metadata is
+        // replaced, as it has to describe the copy.
+        let data: Value = self
+            .document_store(id)
+            .fetch()
+            .await?
+            .ok_or(StoreError::DocumentNotFound(id))?;
+        self.document_store(info.id)
+            .sync_document(&data, metadata(&info))
+            .await?;
+
+        index.documents.push(info.clone());
+        self.index_store().sync(&index).await?;
+
+        Ok(info)
+    }
+
+    /// Deletes the document `id`. Does nothing if there is no such document.
+    pub async fn delete(&self, id: Uuid) -> Result<(), StoreError> {
+        let mut index = self.index().await?;
+        index.documents.retain(|info| info.id != id);
+
+        // The index is written first, so it never lists a deleted document
+        self.index_store().sync(&index).await?;
+        self.document_store(id).remove().await
+    }
+}
+
+/// Metadata saved together with a document, which allows to rebuild the index if it gets lost.
+fn metadata(info: &DocumentInfo) -> Map<String, Value> {
+    let mut metadata = Map::new();
+    metadata.insert(String::from("id"), Value::from(info.id.to_string()));
+    metadata.insert(String::from("title"), Value::from(info.title.clone()));
+    metadata.insert(String::from("created"), Value::from(info.created));
+    metadata.insert(String::from("modified"), Value::from(info.modified));
+
+    metadata
+}
+
+/// Milliseconds since the unix epoch
+#[cfg(target_arch = "wasm32")]
+fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Milliseconds since the unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64() * 1000.)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use futures::executor::block_on;
+
+    use super::Library;
+    use crate::store::{MemoryBackend, StoreError};
+
+    #[test]
+    fn library_manages_documents() {
+        let library = Library::new("maps", Rc::new(MemoryBackend::new()));
+
+        block_on(async {
+            let first = library.create("First", &vec![1, 2], None).await.unwrap();
+            let second = library
+                .create("Second", &vec![3], Some(String::from("data:,")))
+                .await
+                .unwrap();
+            assert_eq!(library.list().await.unwrap().len(), 2);
+            assert!(library.recent(5).await.unwrap().is_empty());
+
+            assert_eq!(
+                library.open::<Vec<i32>>(first.id).await.unwrap(),
+                vec![1, 2]
+            );
+            let recent = library.recent(5).await.unwrap();
+            assert_eq!(recent.len(), 1);
+            assert_eq!(recent[0].id, first.id);
+
+            library.rename(second.id, "Renamed").await.unwrap();
+            let copy = library.duplicate(second.id, "Copy").await.unwrap();
+            assert_eq!(copy.thumbnail.as_deref(), Some("data:,"));
+            assert_eq!(library.open::<Vec<i32>>(copy.id).await.unwrap(), vec![3]);
+
+            let envelope = library
+                .document_store(copy.id)
+                .fetch_document::<Vec<i32>>()
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(envelope.metadata["id"], copy.id.to_string());
+            assert_eq!(envelope.metadata["title"], "Copy");
+
+            library.delete(second.id).await.unwrap();
+            let mut titles: Vec<String> = library
+                .list()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|info| info.title)
+                .collect();
+            titles.sort();
+            assert_eq!(titles, vec!["Copy", "First"]);
+
+            assert!(matches!(
+                library.open::<Vec<i32>>(second.id).await,
+                Err(StoreError::DocumentNotFound(_))
+            ));
+        });
+    }
+}
@@ -0,0 +1,96 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::{StorageBackend, StorageFuture, StoreError};
+
+/// Marks values that are saved base64 encoded. A zero character never starts a json document.
+const BASE64_PREFIX: &str = "\0base64,";
+
+/// Local storage only holds strings, so values that are not valid utf-8, e.g. binary documents, are saved base64
+/// encoded.
+fn to_string(value: Vec<u8>) -> String {
+    match String::from_utf8(value) {
+        Ok(value) if !value.starts_with(BASE64_PREFIX) => value,
+        Ok(value) => format!("{}{}", BASE64_PREFIX, STANDARD.encode(value)),
+        Err(e) => format!("{}{}", BASE64_PREFIX, STANDARD.encode(e.as_bytes())),
+    }
+}
+
+fn from_string(value: String) -> Result<Vec<u8>, StoreError> {
+    match value.strip_prefix(BASE64_PREFIX) {
+        Some(encoded) => STANDARD
+            .decode(encoded)
+            .map_err(|e| StoreError::UnknownFormat(format!("invalid base64: {}", e))),
+        None => Ok(value.into_bytes()),
+    }
+}
+
+/// Saves values to the local storage of the browser. All operations complete immediately, but the storage is limited
+/// to a few megabytes per origin.
+pub struct LocalStorageBackend {
+    local_storage: web_sys::Storage,
+}
+
+impl LocalStorageBackend {
+    /// Fails with [StoreError::Unavailable] if the local storage is not accessible.
+    pub fn new() -> Result<Self, StoreError> {
+        let unavailable = |reason: &str| StoreError::Unavailable {
+            reason: String::from(reason),
+        };
+
+        let window = web_sys::window().ok_or_else(|| unavailable("there is no window"))?;
+        match window.local_storage() {
+            Ok(Some(local_storage)) => Ok(LocalStorageBackend { local_storage }),
+            _ => Err(unavailable("local storage is disabled")),
+        }
+    }
+}
+
+impl StorageBackend for LocalStorageBackend {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            self.local_storage
+                .get_item(key)?
+                .map(from_string)
+                .transpose()
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        Box::pin(async move { Ok(self.local_storage.set_item(key, &to_string(value))?) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move { Ok(self.local_storage.remove_item(key)?) })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let length = self.local_storage.length()?;
+
+            let mut keys = Vec::with_capacity(length as usize);
+            for index in 0..length {
+                if let Some(key) = self.local_storage.key(index)? {
+                    keys.push(key);
+                }
+            }
+
+            Ok(keys)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_string, to_string};
+
+    #[test]
+    fn binary_values_survive_strings() {
+        for value in [
+            b"[1, 2]".to_vec(),
+            vec![0, 159, 146, 150],
+            b"\0base64,AAAA".to_vec(),
+        ] {
+            assert_eq!(from_string(to_string(value.clone())).unwrap(), value);
+        }
+    }
+}
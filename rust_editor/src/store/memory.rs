@@ -0,0 +1,37 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use super::{StorageBackend, StorageFuture};
+
+/// Keeps all values in memory, which is useful for tests and native builds. Clones share the same values.
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    values: Rc<RefCell<BTreeMap<String, Vec<u8>>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        let value = self.values.borrow().get(key).cloned();
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        self.values.borrow_mut().insert(key.to_string(), value);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        self.values.borrow_mut().remove(key);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        let keys = self.values.borrow().keys().cloned().collect();
+        Box::pin(async move { Ok(keys) })
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{from_value, StoreError};
+
+/// Version of the envelope format itself, independent of the version of the data inside it
+pub const ENVELOPE_FORMAT: u32 = 1;
+
+const ENVELOPE_KEY: &str = "$envelope";
+
+/// Wraps saved data together with the application and schema version it was saved with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Envelope<T> {
+    #[serde(rename = "$envelope")]
+    pub format: u32,
+
+    /// Identifier of the application that saved the data. Empty if unknown.
+    pub app: String,
+
+    /// Schema version of `data`
+    pub version: u32,
+
+    /// Additional information about the data, e.g. a title or the time it was saved
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+
+    pub data: T,
+}
+
+impl Envelope<Value> {
+    /// Reads an envelope from a json value. Values without an envelope were saved before envelopes were introduced and
+    /// are treated as version 0 of an unknown application.
+    pub fn from_json(value: Value) -> Result<Self, StoreError> {
+        match value.get(ENVELOPE_KEY) {
+            Some(_) => from_value(value),
+            None => Ok(Envelope {
+                format: ENVELOPE_FORMAT,
+                app: String::new(),
+                version: 0,
+                metadata: Map::new(),
+                data: value,
+            }),
+        }
+    }
+}
+
+/// The fields of an [Envelope] without the data, used to check whether the data needs to be migrated.
+#[derive(Deserialize)]
+pub(crate) struct EnvelopeHeader {
+    #[serde(rename = "$envelope")]
+    _format: u32,
+
+    app: String,
+    version: u32,
+}
+
+type Migration = Box<dyn Fn(Value) -> Result<Value, String>>;
+
+/// Upgrades saved data to the current schema version of the application.
+///
+/// Each migration converts the data of one version into the next version. Loading data of an older version runs all
+/// migrations in between, data of a newer version than the current one is rejected.
+///
+/// # Example
+///
+/// ```
+/// let mut migrations = MigrationRegistry::new("map_editor", 2);
+///
+/// // Version 1 renamed `streets` to `roads`
+/// migrations.register(0, |mut value| {
+///     let streets = value["streets"].take();
+///     value["roads"] = streets;
+///     Ok(value)
+/// });
+///
+/// // Version 2 added a name to every road
+/// migrations.register(1, |mut value| {
+///     for road in value["roads"].as_array_mut().ok_or("roads are not a list")? {
+///         road["name"] = Value::from("");
+///     }
+///     Ok(value)
+/// });
+///
+/// store.set_migrations(Rc::new(migrations));
+/// ```
+#[derive(Default)]
+pub struct MigrationRegistry {
+    app: String,
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl MigrationRegistry {
+    /// Creates a registry for data of `app` whose current schema version is `version`.
+    pub fn new(app: &str, version: u32) -> Self {
+        MigrationRegistry {
+            app: String::from(app),
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    pub fn app(&self) -> &str {
+        &self.app
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Registers the migration that converts data of version `from` into version `from + 1`. Replaces a migration that
+    /// was registered for the same version before.
+    pub fn register(
+        &mut self,
+        from: u32,
+        migration: impl Fn(Value) -> Result<Value, String> + 'static,
+    ) {
+        self.migrations.insert(from, Box::new(migration));
+    }
+
+    /// Wraps `data` into an envelope of the current version.
+    pub fn envelope<T>(&self, data: T, metadata: Map<String, Value>) -> Envelope<T> {
+        Envelope {
+            format: ENVELOPE_FORMAT,
+            app: self.app.clone(),
+            version: self.version,
+            metadata,
+            data,
+        }
+    }
+
+    /// Returns true if data with `header` can be used without migrating it.
+    pub(crate) fn is_current(&self, header: &EnvelopeHeader) -> bool {
+        header.version == self.version && self.accepts(&header.app)
+    }
+
+    fn accepts(&self, app: &str) -> bool {
+        self.app.is_empty() || app.is_empty() || app == self.app
+    }
+
+    /// Converts the data of `envelope` into the current version.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the envelope belongs to another application, is newer than the current version or a migration is
+    /// missing or fails.
+    pub fn migrate(&self, mut envelope: Envelope<Value>) -> Result<Envelope<Value>, StoreError> {
+        if !self.accepts(&envelope.app) {
+            return Err(StoreError::WrongApplication {
+                found: envelope.app,
+                expected: self.app.clone(),
+            });
+        }
+
+        if envelope.version > self.version {
+            return Err(StoreError::VersionMismatch {
+                found: envelope.version,
+                supported: self.version,
+            });
+        }
+
+        while envelope.version < self.version {
+            let migration =
+                self.migrations
+                    .get(&envelope.version)
+                    .ok_or(StoreError::MissingMigration {
+                        version: envelope.version,
+                    })?;
+
+            envelope.data = migration(envelope.data).map_err(|reason| StoreError::Migration {
+                version: envelope.version,
+                reason,
+            })?;
+            envelope.version += 1;
+        }
+
+        envelope.app = self.app.clone();
+
+        Ok(envelope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::{Envelope, MigrationRegistry};
+    use crate::store::StoreError;
+
+    fn registry() -> MigrationRegistry {
+        let mut migrations = MigrationRegistry::new("test", 2);
+        migrations.register(0, |value| Ok(json!({ "values": value })));
+        migrations.register(1, |mut value| {
+            value["count"] = json!(value["values"].as_array().ok_or("no list")?.len());
+            Ok(value)
+        });
+
+        migrations
+    }
+
+    #[test]
+    fn migrates_legacy_data() {
+        let envelope = Envelope::from_json(json!([1, 2])).unwrap();
+        assert_eq!(envelope.version, 0);
+
+        let envelope = registry().migrate(envelope).unwrap();
+        assert_eq!(envelope.version, 2);
+        assert_eq!(envelope.data, json!({ "values": [1, 2], "count": 2 }));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let envelope = Envelope {
+            version: 3,
+            ..registry().envelope(json!(null), Map::new())
+        };
+
+        assert!(matches!(
+            registry().migrate(envelope),
+            Err(StoreError::VersionMismatch {
+                found: 3,
+                supported: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_failed_migrations() {
+        let envelope = Envelope {
+            version: 1,
+            ..registry().envelope(json!({ "values": 1 }), Map::new())
+        };
+
+        assert!(matches!(
+            registry().migrate(envelope),
+            Err(StoreError::Migration { version: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,342 @@
+use std::{future::Future, pin::Pin, rc::Rc};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use uuid::Uuid;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::DomException;
+
+mod encoding;
+mod indexed_db;
+mod library;
+mod local_storage;
+mod memory;
+mod migration;
+mod queue;
+
+pub use self::encoding::{Compression, Encoding, Format};
+pub use self::indexed_db::IndexedDbBackend;
+pub use self::library::{DocumentInfo, Library};
+pub use self::local_storage::LocalStorageBackend;
+pub use self::memory::MemoryBackend;
+pub use self::migration::{Envelope, MigrationRegistry, ENVELOPE_FORMAT};
+pub use self::queue::TaskQueue;
+
+use self::migration::EnvelopeHeader;
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("storage is not available: {reason}")]
+    Unavailable { reason: String },
+
+    #[error("storage quota exceeded. Free some space or use a storage with a larger quota.")]
+    QuotaExceeded,
+
+    #[error("could not serialize data: {0}")]
+    Serialization(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("could not deserialize data at {path}: {source}")]
+    Deserialization {
+        path: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("data is saved in an unknown format: {0}")]
+    UnknownFormat(String),
+
+    #[error("data has version {found}, but only versions up to {supported} are supported. Update the application to open it.")]
+    VersionMismatch { found: u32, supported: u32 },
+
+    #[error("data was saved by {found:?} and cannot be opened by {expected:?}")]
+    WrongApplication { found: String, expected: String },
+
+    #[error("no migration from version {version} is registered")]
+    MissingMigration { version: u32 },
+
+    #[error("could not migrate data from version {version}: {reason}")]
+    Migration { version: u32, reason: String },
+
+    #[error("document {0} does not exist")]
+    DocumentNotFound(Uuid),
+
+    #[error("storage operation failed: {0}")]
+    Backend(String),
+}
+
+impl From<JsValue> for StoreError {
+    fn from(value: JsValue) -> Self {
+        match value.dyn_ref::<DomException>() {
+            Some(exception) if exception.name() == "QuotaExceededError" => {
+                StoreError::QuotaExceeded
+            }
+            Some(exception) => StoreError::Backend(exception.message()),
+            None => StoreError::Backend(format!("{:?}", value)),
+        }
+    }
+}
+
+/// Future returned by the methods of a [StorageBackend]. Backends run on the main thread of the browser, so the future
+/// does not need to be `Send`.
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StoreError>> + 'a>>;
+
+/// Key value storage that documents are saved to.
+pub trait StorageBackend {
+    /// Returns the value stored under `key` or `None` if there is none.
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()>;
+
+    /// Removes the value stored under `key`. Does nothing if there is none.
+    fn delete<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()>;
+
+    /// Returns the keys of all stored values.
+    fn list(&self) -> StorageFuture<'_, Vec<String>>;
+}
+
+/// Saves a value under a single key of a [StorageBackend].
+///
+/// The value is wrapped into an [Envelope] holding the schema version of the data. When loading, data of older versions
+/// is upgraded with the migrations of the store, see [Store::set_migrations].
+///
+/// Values are saved as json unless another [Format] is set. Loading detects the format of the saved value, so changing
+/// it does not break existing saves.
+///
+/// # Example
+///
+/// ```
+/// let backend = Rc::new(IndexedDbBackend::new("editor", "documents"));
+/// let mut store = Store::with_backend("map", backend);
+/// store.set_format(Format::new(Encoding::MessagePack, Compression::Deflate));
+///
+/// store.sync(editor.data()).await?;
+/// let map: Option<Map> = store.fetch().await?;
+/// ```
+pub struct Store {
+    backend: Rc<dyn StorageBackend>,
+    name: String,
+    migrations: Rc<MigrationRegistry>,
+    format: Format,
+}
+
+impl Store {
+    /// Creates a store that saves to the local storage of the browser.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [StoreError::Unavailable] if the local storage is not accessible, e.g. because the user disabled it.
+    pub fn new(name: &str) -> Result<Store, StoreError> {
+        let backend = LocalStorageBackend::new()?;
+
+        Ok(Store::with_backend(name, Rc::new(backend)))
+    }
+
+    pub fn with_backend(name: &str, backend: Rc<dyn StorageBackend>) -> Store {
+        Store {
+            backend,
+            name: String::from(name),
+            migrations: Rc::default(),
+            format: Format::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn backend(&self) -> Rc<dyn StorageBackend> {
+        Rc::clone(&self.backend)
+    }
+
+    /// Sets the application id, the current schema version and the migrations used to load older data. Without it the
+    /// data is saved as version 0 of an unknown application.
+    pub fn set_migrations(&mut self, migrations: Rc<MigrationRegistry>) {
+        self.migrations = migrations;
+    }
+
+    /// Sets the format values are saved in.
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Reads the stored value. Returns `None` if nothing was stored so far.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend cannot be read, the value cannot be migrated to the current version or does not match `T`.
+    /// In the latter case the error contains the path of the first value that did not match.
+    pub async fn fetch<T: DeserializeOwned>(&self) -> Result<Option<T>, StoreError> {
+        Ok(self.fetch_document().await?.map(|envelope| envelope.data))
+    }
+
+    /// Reads the stored value together with its metadata, see [Store::fetch].
+    pub async fn fetch_document<T: DeserializeOwned>(
+        &self,
+    ) -> Result<Option<Envelope<T>>, StoreError> {
+        let bytes = match self.backend.get(&self.name).await? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let (format, payload) = Format::decode(&bytes)?;
+
+        // Data of the current version is deserialized directly, which saves building a json value of large documents
+        if let Ok(header) = format.encoding.deserialize::<EnvelopeHeader>(&payload) {
+            if self.migrations.is_current(&header) {
+                let mut envelope = format.encoding.deserialize::<Envelope<T>>(&payload)?;
+                envelope.app = self.migrations.app().to_string();
+
+                return Ok(Some(envelope));
+            }
+        }
+
+        let value = format.encoding.deserialize::<Value>(&payload)?;
+        let envelope = self.migrations.migrate(Envelope::from_json(value)?)?;
+        Ok(Some(Envelope {
+            format: envelope.format,
+            app: envelope.app,
+            version: envelope.version,
+            metadata: envelope.metadata,
+            data: from_value(envelope.data)?,
+        }))
+    }
+
+    /// Writes `data`, replacing the stored value.
+    pub async fn sync<T: Serialize>(&self, data: &T) -> Result<(), StoreError> {
+        self.sync_document(data, Map::new()).await
+    }
+
+    /// Writes `data` together with `metadata`, replacing the stored value.
+    pub async fn sync_document<T: Serialize>(
+        &self,
+        data: &T,
+        metadata: Map<String, Value>,
+    ) -> Result<(), StoreError> {
+        let envelope = self.migrations.envelope(data, metadata);
+        let value = self.format.encode(&envelope)?;
+
+        self.backend.put(&self.name, value).await
+    }
+
+    /// Deletes the stored value.
+    pub async fn remove(&self) -> Result<(), StoreError> {
+        self.backend.delete(&self.name).await
+    }
+}
+
+/// Converts a json value read from a store into `T`. Like [Store::fetch] it fails with the path of the first value that
+/// did not match `T`.
+pub fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, StoreError> {
+    serde_path_to_error::deserialize(value).map_err(|e| StoreError::Deserialization {
+        path: e.path().to_string(),
+        source: Box::new(e.into_inner()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use futures::executor::block_on;
+
+    use serde_json::json;
+
+    use super::{
+        Compression, Encoding, Format, MemoryBackend, MigrationRegistry, StorageBackend, Store,
+        StoreError,
+    };
+
+    #[test]
+    fn store_saves_to_backend() {
+        let backend = MemoryBackend::new();
+        let store = Store::with_backend("numbers", Rc::new(backend.clone()));
+
+        block_on(async {
+            assert_eq!(store.fetch::<Vec<i32>>().await.unwrap(), None);
+
+            store.sync(&vec![1, 2, 3]).await.unwrap();
+            assert_eq!(
+                store.fetch::<Vec<i32>>().await.unwrap(),
+                Some(vec![1, 2, 3])
+            );
+            assert_eq!(backend.list().await.unwrap(), vec!["numbers".to_string()]);
+
+            store.remove().await.unwrap();
+            assert_eq!(store.fetch::<Vec<i32>>().await.unwrap(), None);
+            assert!(backend.list().await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn store_reads_values_of_other_formats() {
+        let backend = Rc::new(MemoryBackend::new());
+        let mut store = Store::with_backend("numbers", backend.clone());
+
+        block_on(async {
+            store.sync(&vec![1, 2, 3]).await.unwrap();
+
+            store.set_format(Format::new(Encoding::MessagePack, Compression::Deflate));
+            assert_eq!(
+                store.fetch::<Vec<i32>>().await.unwrap(),
+                Some(vec![1, 2, 3])
+            );
+
+            store.sync(&vec![4, 5]).await.unwrap();
+            assert_ne!(backend.get("numbers").await.unwrap().unwrap()[0], b'{');
+
+            store.set_format(Format::default());
+            assert_eq!(store.fetch::<Vec<i32>>().await.unwrap(), Some(vec![4, 5]));
+        });
+    }
+
+    #[test]
+    fn fetch_reports_path_of_invalid_value() {
+        let backend = MemoryBackend::new();
+        let store = Store::with_backend("numbers", Rc::new(backend.clone()));
+
+        block_on(async {
+            backend
+                .put("numbers", b"[1, \"two\", 3]".to_vec())
+                .await
+                .unwrap();
+
+            match store.fetch::<Vec<i32>>().await {
+                Err(StoreError::Deserialization { path, .. }) => assert_eq!(path, "[1]"),
+                other => panic!("unexpected result {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn fetch_migrates_older_versions() {
+        let backend = MemoryBackend::new();
+        let mut store = Store::with_backend("numbers", Rc::new(backend.clone()));
+
+        let mut migrations = MigrationRegistry::new("test", 1);
+        migrations.register(0, |value| Ok(json!({ "values": value })));
+        store.set_migrations(Rc::new(migrations));
+
+        block_on(async {
+            // Saved before the store had an envelope
+            backend.put("numbers", b"[1, 2, 3]".to_vec()).await.unwrap();
+
+            let document = store.fetch_document::<serde_json::Value>().await.unwrap();
+            let document = document.unwrap();
+            assert_eq!(document.app, "test");
+            assert_eq!(document.version, 1);
+            assert_eq!(document.data, json!({ "values": [1, 2, 3] }));
+
+            // Saved by a newer version of the application
+            store.sync(&document.data).await.unwrap();
+            store.set_migrations(Rc::new(MigrationRegistry::new("test", 0)));
+            assert!(matches!(
+                store.fetch::<serde_json::Value>().await,
+                Err(StoreError::VersionMismatch { .. })
+            ));
+        });
+    }
+}
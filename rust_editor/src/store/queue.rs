@@ -0,0 +1,45 @@
+use std::{cell::RefCell, collections::VecDeque, future::Future, pin::Pin, rc::Rc};
+
+use wasm_bindgen_futures::spawn_local;
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+#[derive(Default)]
+struct Tasks {
+    pending: VecDeque<Task>,
+    running: bool,
+}
+
+/// Runs storage tasks one after another. Otherwise tasks that read and write the same value could interleave when the
+/// backend is asynchronous, e.g. two tasks updating the same index. Clones share the same queue.
+#[derive(Clone, Default)]
+pub struct TaskQueue {
+    tasks: Rc<RefCell<Tasks>>,
+}
+
+impl TaskQueue {
+    /// Runs `task` after all previously spawned tasks finished.
+    pub fn spawn(&self, task: impl Future<Output = ()> + 'static) {
+        let mut tasks = self.tasks.borrow_mut();
+        tasks.pending.push_back(Box::pin(task));
+
+        if tasks.running {
+            return;
+        }
+        tasks.running = true;
+
+        let tasks = Rc::clone(&self.tasks);
+        spawn_local(async move {
+            loop {
+                // The queue must not be borrowed while the task runs, since it may spawn further tasks
+                let task = tasks.borrow_mut().pending.pop_front();
+                match task {
+                    Some(task) => task.await,
+                    None => break,
+                }
+            }
+
+            tasks.borrow_mut().running = false;
+        });
+    }
+}
@@ -12,6 +12,14 @@ pub struct Style {
 
     /// The fill color for an element. Accepts all values in css format (RGB, RGBA, #000000, HSL etc.) see https://developer.mozilla.org/en-US/docs/Web/CSS/color_value
     pub background_color: String,
+
+    /// The color of text, see [Text](crate::renderer::Text). Accepts all values in css format (RGB, RGBA, #000000, HSL etc.) see https://developer.mozilla.org/en-US/docs/Web/CSS/color_value
+    #[serde(default = "default_text_color")]
+    pub text_color: String,
+}
+
+fn default_text_color() -> String {
+    "#000000".to_string()
 }
 
 impl Default for Style {
@@ -20,6 +28,7 @@ impl Default for Style {
             border_width: 2,
             border_color: "#0000FF".to_string(),
             background_color: "#FFFFFFF".to_string(),
+            text_color: default_text_color(),
         }
     }
 }
@@ -39,16 +48,19 @@ impl Default for InteractiveElementStyle {
                 border_width: 0,
                 border_color: "#FFFFFF".to_string(),
                 background_color: "#2A2A2B".to_string(),
+                text_color: "#FFFFFF".to_string(),
             },
             hover: Style {
                 border_width: 0,
                 border_color: "".to_string(),
                 background_color: "#1e88e5".to_string(),
+                text_color: "#FFFFFF".to_string(),
             },
             selected: Style {
                 border_width: 0,
                 border_color: "".to_string(),
                 background_color: "hsl(0, 100%, 50%)".to_string(),
+                text_color: "#FFFFFF".to_string(),
             },
         }
     }
@@ -66,16 +78,19 @@ impl InteractiveElementStyle {
                 border_width: 0,
                 border_color: "#FFFFFF".to_string(),
                 background_color: format!("rgb({},{},{})", r, g, b),
+                text_color: "#FFFFFF".to_string(),
             },
             hover: Style {
                 border_width: 0,
                 border_color: "".to_string(),
                 background_color: "#1e88e5".to_string(),
+                text_color: "#FFFFFF".to_string(),
             },
             selected: Style {
                 border_width: 0,
                 border_color: "".to_string(),
                 background_color: "hsl(0, 100%, 50%)".to_string(),
+                text_color: "#FFFFFF".to_string(),
             },
         } 
     }
@@ -3,23 +3,40 @@ use rust_internal::PluginExecutionBehaviour;
 use std::any::Any;
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
+use std::error::Error as StdError;
 use std::rc::Rc;
 use thiserror::Error;
+use gloo::events::EventListener;
 use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
 use yew::html::Scope;
 
+use crate::actions::{Action, MultiAction, Undo};
+use crate::export::{
+    self, svg, ExportError, ExportEvent, ExportOptions, ExportRegistry, ExportedFile, ExporterInfo,
+    JSON_EXPORTER, PNG_EXPORTER, SVG_EXPORTER,
+};
+use crate::history::History;
+use crate::import::{self, ImportError, ImportEvent, ImportRegistry, ImportedFile};
+use crate::snapping::Snapping;
+use crate::snapshot::Recorder;
+use crate::store::StoreError;
 use crate::input::keyboard::Key;
 //use crate::plugins::camera::Camera;
 //use crate::plugins::plugin::{PluginWithOptions, SpecialKey};
 
 use crate::error;
 use crate::plugin::{PluginWithOptions, SpecialKey};
-use geo::Coordinate;
+use crate::style::Style;
+use geo::{Coordinate, Geometry, Rect};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
 use web_sys::{
-    CanvasRenderingContext2d, DragEvent, HtmlCanvasElement, KeyboardEvent, MouseEvent, PointerEvent,
+    CanvasRenderingContext2d, DragEvent, File, FileList, HtmlCanvasElement, HtmlInputElement,
+    KeyboardEvent, MouseEvent, PointerEvent,
 };
 
-use yew::{html, AppHandle, Component, Context, Html, NodeRef, Properties};
+use yew::{html, AppHandle, Callback, Component, Context, Html, NodeRef, Properties};
 
 pub enum EditorMessages<Data> {
     AddPlugin(
@@ -41,6 +58,11 @@ pub enum EditorMessages<Data> {
     MouseMove(MouseEvent),
     MouseDown(MouseEvent),
     MouseUp(MouseEvent),
+
+    /// The mouse button was released anywhere in the window or the window lost the focus. Ends the gesture that
+    /// began with the last mouse down on the canvas, see [App::set_recorder]
+    EndGesture,
+
     KeyDown(KeyboardEvent),
     KeyUp(KeyboardEvent),
     ShortkeyPressed(Shortkey),
@@ -48,6 +70,17 @@ pub enum EditorMessages<Data> {
     UpdateElements(),
     Drop(DragEvent),
     DragOver(DragEvent),
+
+    /// Reads the files and imports them with the registered importers
+    Import(Vec<File>),
+    FileRead(Result<ImportedFile, ImportError>),
+
+    /// Exports with the exporter of the given name and downloads the file or hands it to the host
+    Export(&'static str, ExportOptions),
+
+    /// Saving or loading with a [Store](crate::store::Store) failed. Reported to the listeners registered with
+    /// [App::listen_store_failures], since stores run asynchronously and cannot access the editor themselves.
+    StoreFailed(StoreError),
     RerenderView,
 }
 
@@ -65,6 +98,12 @@ pub enum EditorError {
 
     #[error("toolbar with id {:?} is already registered.", id)]
     ToolbarExists { id: &'static str },
+
+    #[error("a transaction is already active. Commit or rollback it before beginning a new one.")]
+    TransactionActive,
+
+    #[error("no transaction is active. Make sure to begin one before pushing actions to it.")]
+    NoActiveTransaction,
 }
 
 pub type Plugins<Data> = BTreeMap<PluginId, Rc<RefCell<dyn PluginWithOptions<Data>>>>;
@@ -77,17 +116,63 @@ where
     /// Holds the displayed data
     data: Data,
 
+    /// All executed actions that can be undone or redone
+    history: History<Data>,
+
+    /// Actions executed since the current transaction began
+    active_transaction: Option<MultiAction<Data>>,
+
+    /// Turns direct changes of the data into undoable actions, see [App::set_recorder]
+    recorder: Option<Box<dyn Recorder<Data>>>,
+
+    /// Number of nested transactions and gestures the recorder currently records
+    recording: usize,
+
+    /// True between mouse down and mouse up while a recorder is set
+    recording_gesture: bool,
+
+    /// True once the recorder saw the data before its first change of the current recording
+    recording_changes: bool,
+
+    /// Changes of the active gesture made before the active transaction began. The transaction is recorded on its own,
+    /// so that rolling it back reverts exactly its changes.
+    gesture_checkpoint: Option<Rc<RefCell<dyn Action<Data>>>>,
+
+    /// Changes made outside of the editor that wait for the active transaction or gesture to end, see
+    /// [App::apply_external]
+    external_changes: Vec<Box<dyn FnOnce(&mut Data)>>,
+
     /// All plugins that implement the editor logic and functionality
     plugins: Plugins<Data>,
 
     /// Registered by plugins, shortkeys must by unique.
     shortkeys: HashMap<PluginId, Vec<Shortkey>>,
 
+    /// Importers registered by plugins, see [App::register_importer]
+    importers: ImportRegistry<Data>,
+
+    /// Exporters registered by plugins and the host, see [App::register_exporter]
+    exporters: ExportRegistry<Data>,
+
+    /// Shared by gizmos and tools to snap dragged points, see [App::snapping]
+    snapping: Rc<RefCell<Snapping>>,
+
+    /// Called for each [EditorMessages::StoreFailed], see [App::listen_store_failures]
+    store_listeners: Vec<Box<dyn Fn(&StoreError)>>,
+
     /// Black magic needed by yew
     _render_loop: Option<AnimationFrame>,
+    link: Option<Scope<Self>>,
     canvas_ref: NodeRef,
     context: Option<CanvasRenderingContext2d>,
 
+    /// Ends gestures whose mouse up happens outside of the canvas
+    _gesture_listeners: Vec<EventListener>,
+
+    /// Waits for files chosen with [App::choose_import_files]. Replaced on the next call, so dialogs the user cancelled do
+    /// not pile up.
+    import_listener: Option<EventListener>,
+
     /// Internally stores the pressed keys as registered by native web events.
     /// Keys are pushed to the end so the vec is sorted from oldest pressed key to newest
     pressed_keys: Vec<Key>,
@@ -105,11 +190,26 @@ where
     fn default() -> Self {
         Self {
             data: Default::default(),
+            history: Default::default(),
+            active_transaction: None,
+            recorder: None,
+            recording: 0,
+            recording_gesture: false,
+            recording_changes: false,
+            gesture_checkpoint: None,
+            external_changes: Vec::new(),
             plugins: Default::default(),
             shortkeys: Default::default(),
+            importers: Default::default(),
+            exporters: default_exporters(),
+            snapping: Default::default(),
+            store_listeners: Vec::new(),
             _render_loop: Default::default(),
+            link: None,
             canvas_ref: Default::default(),
             context: Default::default(),
+            _gesture_listeners: Vec::new(),
+            import_listener: None,
             pressed_keys: Default::default(),
             canvas_size: Default::default(),
             last_mouse_pos: Coordinate { x: 0., y: 0. },
@@ -128,14 +228,486 @@ where
 
     /// Returns a mutable reference to the data hold by the editor.
     pub fn data_mut(&mut self) -> &mut Data {
+        self.record_change();
         &mut self.data
     }
 
     /// Replaces the data hold by the editor by `data`.
     pub fn set_data(&mut self, data: Data) {
+        self.record_change();
         self.data = data
     }
 
+    /// Applies a change that was not made by the user of this editor, e.g. one received from another user editing the
+    /// same data. The change is neither added to the history nor recorded. While a transaction or gesture is active, it
+    /// is delayed until the transaction or gesture ended, so that undoing or rolling back the local changes keeps it.
+    pub fn apply_external(&mut self, f: impl FnOnce(&mut Data) + 'static) {
+        self.external_changes.push(Box::new(f));
+        self.apply_external_changes();
+    }
+
+    fn apply_external_changes(&mut self) {
+        if self.is_recording() || self.active_transaction.is_some() {
+            return;
+        }
+
+        for f in std::mem::take(&mut self.external_changes) {
+            f(&mut self.data);
+        }
+    }
+
+    /// Returns a non mutable reference to the undo / redo history.
+    pub fn history(&self) -> &History<Data> {
+        &self.history
+    }
+
+    /// Returns the snapping service. Gizmos and tools keep a clone of it, so changes to the settings apply to all of
+    /// them.
+    pub fn snapping(&self) -> Rc<RefCell<Snapping>> {
+        Rc::clone(&self.snapping)
+    }
+
+    /// Returns a mutable reference to the undo / redo history.
+    pub fn history_mut(&mut self) -> &mut History<Data> {
+        &mut self.history
+    }
+
+    /// Undoes the most recent action. Returns false if there is nothing to undo or changes are currently recorded.
+    pub fn undo(&mut self) -> bool {
+        !self.is_recording() && self.history.undo(&mut self.data)
+    }
+
+    /// Redoes the most recently undone action. Returns false if there is nothing to redo or changes are currently recorded.
+    pub fn redo(&mut self) -> bool {
+        !self.is_recording() && self.history.redo(&mut self.data)
+    }
+
+    /// Undoes or redoes actions until exactly `position` actions of the history are applied to the data.
+    pub fn jump_in_history(&mut self, position: usize) {
+        if !self.is_recording() {
+            self.history.jump_to(position, &mut self.data)
+        }
+    }
+
+    /// Undoes or redoes actions until the action at `path` of the [undo tree](History::tree) is the most recent applied
+    /// one. Returns false if the node could not be reached.
+    pub fn jump_to_history_node(&mut self, path: &[usize]) -> bool {
+        !self.is_recording() && self.history.jump_to_node(path, &mut self.data)
+    }
+
+    /// Replaces the most recent applied action with its next or previous sibling in the undo tree. Returns false if there
+    /// is no sibling.
+    pub fn switch_history_branch(&mut self, forward: bool) -> bool {
+        !self.is_recording() && self.history.switch_sibling(forward, &mut self.data)
+    }
+
+    /// Returns a callback that sends messages to the editor. Allows plugins to react on asynchronous events, e.g. data
+    /// received over the network. Returns `None` if the editor is not rendered.
+    pub fn message_callback(&self) -> Option<Callback<EditorMessages<Data>>> {
+        self.link
+            .as_ref()
+            .map(|link| link.callback(|message: EditorMessages<Data>| message))
+    }
+
+    /// Renders the data into an image of at most `width` x `height` pixels and returns it as png data url. Overlays like
+    /// gizmos are left out and the aspect ratio of the canvas is kept. Returns `None` if the editor is not rendered.
+    pub fn thumbnail(&self, width: u32, height: u32) -> Option<String> {
+        let canvas = self.canvas_ref.cast::<HtmlCanvasElement>()?;
+        if canvas.width() == 0 || canvas.height() == 0 {
+            return None;
+        }
+
+        let scale = f64::min(
+            width as f64 / canvas.width() as f64,
+            height as f64 / canvas.height() as f64,
+        );
+
+        self.copy_canvas(None, scale)?.to_data_url().ok()
+    }
+
+    /// Returns the size of the canvas in pixels.
+    pub fn canvas_size(&self) -> Coordinate<i32> {
+        self.canvas_size
+    }
+
+    /// Renders the data without the [overlays](crate::plugin::Plugin::render_overlay) of the plugins into a new canvas
+    /// of the size of the editor's canvas. Returns `None` if the editor is not rendered.
+    fn render_scene(&self) -> Option<HtmlCanvasElement> {
+        let canvas = self.canvas_ref.cast::<HtmlCanvasElement>()?;
+        let (scene, context) = create_canvas(canvas.width(), canvas.height())?;
+
+        for plugin in self.plugins.values() {
+            // The plugin that asked for the image, e.g. to save a thumbnail, is borrowed already and left out
+            if let Ok(plugin) = plugin.try_borrow() {
+                if plugin.enabled() {
+                    plugin.render(&context, self);
+                }
+            }
+        }
+
+        Some(scene)
+    }
+
+    /// Renders `region` of the canvas without overlays, or all of it if `region` is `None`, into a new canvas that is
+    /// `scale` times as large. Returns `None` if the editor is not rendered.
+    fn copy_canvas(&self, region: Option<Rect<f64>>, scale: f64) -> Option<HtmlCanvasElement> {
+        let canvas = self.render_scene()?;
+        let region = region.unwrap_or_else(|| {
+            Rect::new(
+                Coordinate { x: 0., y: 0. },
+                Coordinate {
+                    x: canvas.width() as f64,
+                    y: canvas.height() as f64,
+                },
+            )
+        });
+
+        let width = (region.width() * scale).round().max(1.);
+        let height = (region.height() * scale).round().max(1.);
+
+        let (copy, context) = create_canvas(width as u32, height as u32)?;
+        context
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &canvas,
+                region.min().x,
+                region.min().y,
+                region.width(),
+                region.height(),
+                0.,
+                0.,
+                width,
+                height,
+            )
+            .ok()?;
+
+        Some(copy)
+    }
+
+    /// Sets the recorder that turns direct changes of the data into undoable actions. Changes are recorded for each
+    /// transaction and each mouse gesture, i.e. everything between mouse down and mouse up, and added as one step to the
+    /// history. Transactions that run during a gesture are part of the gesture's step.
+    ///
+    /// The recorder only sees the data once it is about to change, i.e. on the first call of [data_mut](App::data_mut),
+    /// [set_data](App::set_data) or [push_action](App::push_action) of a step. Steps that only read the data, e.g. a
+    /// click that selects an element, cost nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///
+    /// editor.set_recorder(SnapshotRecorder::default());
+    ///
+    /// editor.transaction(|editor| {
+    ///     editor.data_mut().streets.push(street);
+    ///
+    ///     Ok(())
+    /// })?;
+    ///
+    /// ```
+    pub fn set_recorder(&mut self, recorder: impl Recorder<Data> + 'static) {
+        self.recorder = Some(Box::new(recorder));
+        self.recording = 0;
+        self.recording_gesture = false;
+        self.recording_changes = false;
+        self.gesture_checkpoint = None;
+        self.apply_external_changes();
+    }
+
+    /// Removes the recorder. Only actions pushed explicitly are added to the history afterwards.
+    pub fn remove_recorder(&mut self) {
+        self.recorder = None;
+        self.recording = 0;
+        self.recording_gesture = false;
+        self.recording_changes = false;
+        self.gesture_checkpoint = None;
+        self.apply_external_changes();
+    }
+
+    /// Returns true if changes of the data are currently recorded by the recorder.
+    pub fn is_recording(&self) -> bool {
+        self.recording > 0
+    }
+
+    fn begin_recording(&mut self) {
+        if self.recorder.is_some() {
+            self.recording += 1;
+        }
+    }
+
+    /// Hands the data to the recorder before it changes for the first time during the current recording.
+    fn record_change(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if self.recording > 0 && !self.recording_changes {
+                recorder.begin(&self.data);
+                self.recording_changes = true;
+            }
+        }
+    }
+
+    /// Begins recording a mouse gesture, which lasts until the mouse button is released.
+    fn begin_gesture(&mut self) {
+        if self.recorder.is_some() && !self.recording_gesture {
+            self.recording_gesture = true;
+            self.begin_recording();
+        }
+    }
+
+    fn end_gesture(&mut self) {
+        if self.recording_gesture {
+            self.recording_gesture = false;
+            self.end_recording(true);
+            self.apply_external_changes();
+        }
+    }
+
+    /// Ends one level of recording. If it was the outermost one, the recorded changes are added to the history if `keep`
+    /// is true and reverted otherwise.
+    fn end_recording(&mut self, keep: bool) {
+        let recorder = match self.recorder.as_mut() {
+            Some(recorder) if self.recording > 0 => recorder,
+            _ => return,
+        };
+
+        self.recording -= 1;
+        if self.recording > 0 || !std::mem::take(&mut self.recording_changes) {
+            return;
+        }
+
+        if let Some(action) = recorder.finish(&self.data) {
+            match keep {
+                true => self.history.push_generic(action),
+                false => action.borrow_mut().undo(&mut self.data),
+            }
+        }
+    }
+
+    /// Finishes the recording of the active gesture so far, so that the transaction that begins is recorded on its own.
+    fn checkpoint_gesture(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if std::mem::take(&mut self.recording_changes) {
+                self.gesture_checkpoint = recorder.finish(&self.data);
+            }
+        }
+    }
+
+    /// Ends the recording of a transaction during a gesture. The changes of the transaction are reverted unless `keep`
+    /// is true. The gesture keeps recording from where it began, including the kept changes.
+    fn end_transaction_in_gesture(&mut self, keep: bool) {
+        let recorder = match self.recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        self.recording -= 1;
+        let changes = match std::mem::take(&mut self.recording_changes) {
+            true => recorder.finish(&self.data),
+            false => None,
+        };
+        if let (Some(changes), false) = (&changes, keep) {
+            changes.borrow_mut().undo(&mut self.data);
+        }
+
+        let steps: Vec<_> = self
+            .gesture_checkpoint
+            .take()
+            .into_iter()
+            .chain(changes.filter(|_| keep))
+            .collect();
+        if steps.is_empty() {
+            return;
+        }
+
+        // Rewinds the data to the beginning of the gesture, so that the recorder sees it before all of its changes
+        for step in steps.iter().rev() {
+            step.borrow_mut().undo(&mut self.data);
+        }
+        recorder.begin(&self.data);
+        self.recording_changes = true;
+        for step in &steps {
+            step.borrow_mut().redo(&mut self.data);
+        }
+    }
+
+    /// Begins a new transaction. All actions pushed with [push_action](App::push_action) until the transaction is
+    /// committed are grouped and added as one step to the history. If a [recorder](App::set_recorder) is set, all
+    /// changes of the data are added instead.
+    ///
+    /// # Errors
+    ///
+    /// An [TransactionActive](EditorError) error will be returned if another transaction was not committed or rolled back yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///
+    /// editor.begin_transaction()?;
+    /// editor.push_action(CreateStreet::new(start, end))?;
+    /// editor.push_action(CreateIntersection::new(end))?;
+    /// editor.commit_transaction()?;
+    ///
+    /// ```
+    pub fn begin_transaction(&mut self) -> Result<(), EditorError> {
+        if self.active_transaction.is_some() {
+            return Err(EditorError::TransactionActive);
+        }
+
+        self.active_transaction = Some(MultiAction::new());
+        if self.recording_gesture {
+            self.checkpoint_gesture();
+        }
+        self.begin_recording();
+
+        Ok(())
+    }
+
+    /// Returns true if a transaction was begun and not yet committed or rolled back.
+    pub fn has_active_transaction(&self) -> bool {
+        self.active_transaction.is_some()
+    }
+
+    /// Executes `action` on the data and adds it to the active transaction.
+    ///
+    /// # Errors
+    ///
+    /// An [NoActiveTransaction](EditorError) error will be returned if no transaction is active. The action is not executed in this case.
+    pub fn push_action<A>(&mut self, mut action: A) -> Result<(), EditorError>
+    where
+        A: Action<Data> + 'static,
+    {
+        if self.active_transaction.is_none() {
+            return Err(EditorError::NoActiveTransaction);
+        }
+
+        self.record_change();
+        action.execute(&mut self.data);
+        if let Some(transaction) = self.active_transaction.as_mut() {
+            transaction.push(action);
+        }
+
+        Ok(())
+    }
+
+    /// Adds `action`, whose changes were already applied to the data, to the active transaction or, if there is none,
+    /// as one step to the history. Used for actions that describe a finished interaction, e.g. the drag of a
+    /// [gizmo](crate::gizmo::Gizmo::mouse_up).
+    ///
+    /// While a [recorder](App::set_recorder) records, the changes are part of the recording already and the action is
+    /// dropped.
+    pub fn push_executed_action<A>(&mut self, action: A)
+    where
+        A: Action<Data> + 'static,
+    {
+        if self.is_recording() {
+            return;
+        }
+
+        match self.active_transaction.as_mut() {
+            Some(transaction) => transaction.push(action),
+            None => self.history.push(Rc::new(RefCell::new(action))),
+        }
+    }
+
+    /// Ends an interaction on the data with `end` and adds the action it returns to the history, see
+    /// [push_executed_action](App::push_executed_action). Returns whether an action was added.
+    ///
+    /// Meant for the [mouse_up](crate::plugin::Plugin::mouse_up) of plugins that drag elements with a gizmo:
+    ///
+    /// ```ignore
+    /// editor.finish_interaction(|data| gizmo.mouse_up(mouse_pos, button.into(), data.elements.iter_mut()))
+    /// ```
+    pub fn finish_interaction<A>(&mut self, end: impl FnOnce(&mut Data) -> Option<A>) -> bool
+    where
+        A: Action<Data> + 'static,
+    {
+        match end(self.data_mut()) {
+            Some(action) => {
+                self.push_executed_action(action);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ends the active transaction and adds all of its actions as one undoable step to the history.
+    /// Transactions without actions are discarded.
+    ///
+    /// # Errors
+    ///
+    /// An [NoActiveTransaction](EditorError) error will be returned if no transaction is active.
+    pub fn commit_transaction(&mut self) -> Result<(), EditorError> {
+        let transaction = self
+            .active_transaction
+            .take()
+            .ok_or(EditorError::NoActiveTransaction)?;
+
+        // The recorded changes already contain the effects of the pushed actions
+        match self.recording {
+            0 if !transaction.actions.is_empty() => {
+                self.history.push(Rc::new(RefCell::new(transaction)))
+            }
+            0 => {}
+            1 => self.end_recording(true),
+            _ => self.end_transaction_in_gesture(true),
+        }
+        self.apply_external_changes();
+
+        Ok(())
+    }
+
+    /// Ends the active transaction and undoes all of its actions in reverse order. Nothing is added to the history.
+    /// If a [recorder](App::set_recorder) is set, all changes of the data made during the transaction are reverted.
+    ///
+    /// # Errors
+    ///
+    /// An [NoActiveTransaction](EditorError) error will be returned if no transaction is active.
+    pub fn rollback_transaction(&mut self) -> Result<(), EditorError> {
+        let mut transaction = self
+            .active_transaction
+            .take()
+            .ok_or(EditorError::NoActiveTransaction)?;
+
+        match self.recording {
+            0 => transaction.undo(&mut self.data),
+            1 => self.end_recording(false),
+            _ => self.end_transaction_in_gesture(false),
+        }
+        self.apply_external_changes();
+
+        Ok(())
+    }
+
+    /// Runs `f` inside a new transaction. The transaction is committed if `f` succeeds and rolled back otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///
+    /// editor.transaction(|editor| {
+    ///     editor.push_action(CreateStreet::new(start, end))?;
+    ///     editor.push_action(SplitStreet::new(id, position))?;
+    ///
+    ///     Ok(())
+    /// })?;
+    ///
+    /// ```
+    pub fn transaction<F>(&mut self, f: F) -> Result<(), EditorError>
+    where
+        F: FnOnce(&mut App<Data>) -> Result<(), EditorError>,
+    {
+        self.begin_transaction()?;
+
+        match f(self) {
+            Ok(()) => self.commit_transaction(),
+            Err(e) => {
+                if self.has_active_transaction() {
+                    self.rollback_transaction()?;
+                }
+
+                Err(e)
+            }
+        }
+    }
+
     /// Finds a plugin that was registered to the editor instance and let you perform non mutable actions on it.
     /// To perform the action you need to specify a closure `f`.
     ///
@@ -274,6 +846,278 @@ where
     pub fn has_shortkey(&self, key: Shortkey) -> bool {
         self.shortkeys.values().any(|x| x.contains(&&key))
     }
+
+    /// Registers `importer` for files of the given mime types or extensions. Files dropped onto the canvas or chosen with
+    /// [choose_import_files](App::choose_import_files) are read and passed to the first matching importer. Mime types
+    /// may end with a wildcard, e.g. `image/*`, extensions are given without the dot.
+    ///
+    /// The importer runs inside a transaction, so the actions it pushes become one undoable step. Returning an error
+    /// rolls the transaction back.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///
+    /// editor.register_importer("geojson", &["application/geo+json"], &["geojson"], |file, editor| {
+    ///     let streets = parse_geojson(file.text()?)?;
+    ///     editor.push_action(AddStreets::new(streets))?;
+    ///
+    ///     Ok(())
+    /// });
+    ///
+    /// ```
+    pub fn register_importer(
+        &mut self,
+        name: &'static str,
+        mime_types: &[&str],
+        extensions: &[&str],
+        importer: impl Fn(&ImportedFile, &mut App<Data>) -> Result<(), Box<dyn StdError>> + 'static,
+    ) {
+        self.importers.register(name, mime_types, extensions, Rc::new(importer));
+    }
+
+    /// Registers `listener` to be called once for each imported file, whether the import succeeded or not.
+    pub fn listen_imports(&mut self, listener: impl Fn(&ImportEvent) + 'static) {
+        self.importers.listen(listener);
+    }
+
+    /// Imports `file` with the matching importer and reports the result to the [import listeners](App::listen_imports).
+    pub fn import(&mut self, file: ImportedFile) {
+        let result = self.run_importer(&file);
+
+        match &result {
+            Ok(importer) => self.importers.emit(&ImportEvent::Imported {
+                file: &file.name,
+                importer,
+            }),
+            Err(e) => {
+                error!("{}", e);
+                self.importers.emit(&ImportEvent::Failed(e));
+            }
+        }
+    }
+
+    fn run_importer(&mut self, file: &ImportedFile) -> Result<&'static str, ImportError> {
+        let (name, importer) = self
+            .importers
+            .find(file)
+            .ok_or_else(|| ImportError::Unsupported {
+                name: file.name.clone(),
+            })?;
+
+        let failed = |source: Box<dyn StdError>| ImportError::Failed {
+            name: file.name.clone(),
+            source,
+        };
+
+        self.begin_transaction().map_err(|e| failed(Box::new(e)))?;
+
+        match importer(file, self) {
+            Ok(()) => self.commit_transaction().map_err(|e| failed(Box::new(e)))?,
+            Err(e) => {
+                if self.has_active_transaction() {
+                    self.rollback_transaction().map_err(|e| failed(Box::new(e)))?;
+                }
+
+                return Err(failed(e));
+            }
+        }
+
+        Ok(name)
+    }
+
+    /// Reads `files` asynchronously and imports each of them once it was read. Files are imported in the order in
+    /// which reading them finishes.
+    pub fn import_files(&self, files: Vec<File>) {
+        let link = match &self.link {
+            Some(link) => link.clone(),
+            None => return,
+        };
+
+        for file in files {
+            let link = link.clone();
+            spawn_local(async move {
+                link.send_message(EditorMessages::FileRead(import::read_file(file).await));
+            });
+        }
+    }
+
+    /// Registers `exporter` under the name of `info`, replacing an exporter of the same name. A png exporter for the
+    /// canvas is registered by default, see [PNG_EXPORTER].
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///
+    /// editor.register_exporter(
+    ///     ExporterInfo { name: "csv", label: "Streets as CSV", mime_type: "text/csv", extension: "csv" },
+    ///     |editor, _| Ok(streets_to_csv(&editor.data().streets).into_bytes()),
+    /// );
+    ///
+    /// ```
+    pub fn register_exporter(
+        &mut self,
+        info: ExporterInfo,
+        exporter: impl Fn(&App<Data>, &ExportOptions) -> Result<Vec<u8>, Box<dyn StdError>> + 'static,
+    ) {
+        self.exporters.register(info, Rc::new(exporter));
+    }
+
+    /// Registers an exporter that saves the data as json, see [JSON_EXPORTER].
+    pub fn register_json_exporter(&mut self)
+    where
+        Data: Serialize,
+    {
+        self.register_exporter(
+            ExporterInfo {
+                name: JSON_EXPORTER,
+                label: "JSON",
+                mime_type: "application/json",
+                extension: "json",
+            },
+            |editor, _| Ok(serde_json::to_vec_pretty(editor.data())?),
+        );
+    }
+
+    /// Registers an exporter that saves the shapes returned by `shapes` as svg, see [SVG_EXPORTER]. The exported region
+    /// and scale are taken from the [ExportOptions].
+    pub fn register_svg_exporter(
+        &mut self,
+        shapes: impl Fn(&Data) -> Vec<(Geometry<f64>, Style)> + 'static,
+    ) {
+        self.register_exporter(
+            ExporterInfo {
+                name: SVG_EXPORTER,
+                label: "SVG",
+                mime_type: "image/svg+xml",
+                extension: "svg",
+            },
+            move |editor, options| {
+                let view_box = options.region.unwrap_or_else(|| {
+                    Rect::new(
+                        Coordinate { x: 0., y: 0. },
+                        Coordinate {
+                            x: editor.canvas_size.x as f64,
+                            y: editor.canvas_size.y as f64,
+                        },
+                    )
+                });
+
+                Ok(svg::document(&shapes(editor.data()), view_box, options.scale).into_bytes())
+            },
+        );
+    }
+
+    /// Returns all registered exporters, e.g. to list them in a menu.
+    pub fn exporters(&self) -> Vec<ExporterInfo> {
+        self.exporters.exporters()
+    }
+
+    /// Hands exported files to `handler` instead of downloading them. Allows an application that embeds the editor to
+    /// decide where exported files go.
+    pub fn set_export_handler(&mut self, handler: impl Fn(ExportedFile) + 'static) {
+        self.exporters.set_handler(handler);
+    }
+
+    /// Registers `listener` to be called once for each export started with [deliver_export](App::deliver_export).
+    pub fn listen_exports(&mut self, listener: impl Fn(&ExportEvent) + 'static) {
+        self.exporters.listen(listener);
+    }
+
+    /// Registers `listener` to be called each time a plugin reports that saving or loading failed, see
+    /// [EditorMessages::StoreFailed].
+    pub fn listen_store_failures(&mut self, listener: impl Fn(&StoreError) + 'static) {
+        self.store_listeners.push(Box::new(listener));
+    }
+
+    /// Runs the exporter named `name` and returns the exported file.
+    pub fn export(&self, name: &str, options: &ExportOptions) -> Result<ExportedFile, ExportError> {
+        let (info, exporter) = self.exporters.find(name).ok_or_else(|| ExportError::Unknown {
+            name: String::from(name),
+        })?;
+
+        let bytes = exporter(self, options).map_err(|source| ExportError::Failed {
+            name: info.name,
+            source,
+        })?;
+
+        Ok(ExportedFile {
+            name: format!("{}.{}", options.file_name, info.extension),
+            mime_type: String::from(info.mime_type),
+            bytes,
+        })
+    }
+
+    /// Runs the exporter named `name` and hands the file to the [export handler](App::set_export_handler) or downloads
+    /// it if there is none. The result is reported to the [export listeners](App::listen_exports).
+    pub fn deliver_export(&self, name: &str, options: &ExportOptions) {
+        let result = self.export(name, options).and_then(|file| {
+            match self.exporters.handler() {
+                Some(handler) => handler(file.clone()),
+                None => export::download(&file)?,
+            }
+
+            Ok(file)
+        });
+
+        match &result {
+            Ok(file) => self.exporters.emit(&ExportEvent::Exported {
+                exporter: name,
+                file,
+            }),
+            Err(e) => {
+                error!("{}", e);
+                self.exporters.emit(&ExportEvent::Failed(e));
+            }
+        }
+    }
+
+    /// Renders `region` of the canvas, or all of it, as png image scaled by `scale`. Overlays like gizmos are left out.
+    fn export_png(&self, options: &ExportOptions) -> Result<Vec<u8>, Box<dyn StdError>> {
+        let data_url = self
+            .copy_canvas(options.region, options.scale)
+            .ok_or("the editor is not rendered")?
+            .to_data_url_with_type("image/png")
+            .map_err(|e| format!("{:?}", e))?;
+
+        let encoded = data_url
+            .split_once(',')
+            .map(|(_, encoded)| encoded)
+            .ok_or("the canvas returned an invalid data url")?;
+
+        Ok(STANDARD.decode(encoded)?)
+    }
+
+    /// Opens the file chooser of the browser, which only offers files supported by the registered importers. The chosen
+    /// files are imported like dropped ones.
+    pub fn choose_import_files(&mut self) {
+        let link = match &self.link {
+            Some(link) => link.clone(),
+            None => return,
+        };
+
+        let input = match web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.create_element("input").ok())
+            .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        {
+            Some(input) => input,
+            None => return,
+        };
+
+        input.set_type("file");
+        input.set_multiple(true);
+        input.set_accept(&self.importers.accept());
+
+        // The input is not part of the document, the listener keeps it alive until the next dialog is opened. No event is
+        // fired if the user cancels the dialog.
+        let target = input.clone();
+        self.import_listener = Some(EventListener::once(&input, "change", move |_| {
+            link.send_message(EditorMessages::Import(files(target.files())));
+        }));
+
+        input.click();
+    }
 }
 
 #[derive(Properties, PartialEq, Default)]
@@ -286,7 +1130,7 @@ where
     type Message = EditorMessages<Data>;
     type Properties = EditorProps;
 
-    fn create(_: &yew::Context<Self>) -> Self {
+    fn create(ctx: &yew::Context<Self>) -> Self {
         let window = web_sys::window().expect("no global `window` exists");
         let document = window.document().expect("should have a document on window");
         let body = document.body().expect("should have a body");
@@ -295,11 +1139,26 @@ where
 
         App {
             data: Data::default(),
+            history: History::default(),
+            active_transaction: None,
+            recorder: None,
+            recording: 0,
+            recording_gesture: false,
+            recording_changes: false,
+            gesture_checkpoint: None,
+            external_changes: Vec::new(),
             plugins: BTreeMap::new(),
             shortkeys: HashMap::new(),
+            importers: ImportRegistry::default(),
+            exporters: default_exporters(),
+            snapping: Rc::new(RefCell::new(Snapping::default())),
+            store_listeners: Vec::new(),
             canvas_ref: NodeRef::default(),
             _render_loop: None,
+            link: Some(ctx.link().clone()),
             context: None,
+            _gesture_listeners: Vec::new(),
+            import_listener: None,
 
             pressed_keys: Vec::new(),
             canvas_size: Coordinate {
@@ -335,6 +1194,18 @@ where
 
             // A reference to the handle must be stored, otherwise it is dropped and the render won't occur.
             self._render_loop = Some(handle);
+
+            // The canvas misses mouse ups outside of it, the gesture would never end otherwise
+            let window = web_sys::window().expect("no global `window` exists");
+            self._gesture_listeners = ["mouseup", "pointercancel", "blur"]
+                .into_iter()
+                .map(|event| {
+                    let link = ctx.link().clone();
+                    EventListener::new(&window, event, move |_| {
+                        link.send_message(EditorMessages::EndGesture)
+                    })
+                })
+                .collect();
         }
     }
 
@@ -343,6 +1214,11 @@ where
             EditorMessages::Drop(e) => {
                 e.prevent_default();
 
+                let dropped = files(e.data_transfer().and_then(|transfer| transfer.files()));
+                if !dropped.is_empty() {
+                    self.import_files(dropped);
+                }
+
                 for (_, plugin) in enabled_plugins(&mut self.plugins) {
                     plugin.as_ref().borrow_mut().drop(e.clone())
                 }
@@ -350,6 +1226,25 @@ where
             EditorMessages::DragOver(e) => {
                 e.prevent_default();
             }
+            EditorMessages::Import(files) => self.import_files(files),
+            EditorMessages::Export(name, options) => self.deliver_export(name, &options),
+            EditorMessages::StoreFailed(e) => {
+                error!("{}", e);
+                for listener in &self.store_listeners {
+                    listener(&e);
+                }
+            }
+            EditorMessages::FileRead(result) => {
+                match result {
+                    Ok(file) => self.import(file),
+                    Err(e) => {
+                        error!("{}", e);
+                        self.importers.emit(&ImportEvent::Failed(&e));
+                    }
+                }
+
+                return true;
+            }
             EditorMessages::UpdateElements() => return true,
             EditorMessages::AddPlugin((key, plugin)) => {
                 if let Err(e) = plugin.as_ref().borrow_mut().startup(self) {
@@ -393,6 +1288,8 @@ where
 
                 self.last_mouse_pos = mouse_pos;
 
+                self.begin_gesture();
+
                 let enabled_plugins = enabled_plugins(&mut self.plugins);
                 for (_, plugin) in &enabled_plugins {
                     if plugin
@@ -418,7 +1315,10 @@ where
                         break;
                     }
                 }
+
+                self.end_gesture();
             }
+            EditorMessages::EndGesture => self.end_gesture(),
             EditorMessages::KeyDown(e) => {
                 e.prevent_default();
 
@@ -532,12 +1432,11 @@ where
                     return true;
                 }
 
-                self.plugins
-                    .get_mut(plugin_id)
-                    .unwrap()
+                let plugin = Rc::clone(self.plugins.get(plugin_id).unwrap());
+                plugin
                     .as_ref()
                     .borrow_mut()
-                    .on_message(message);
+                    .on_message_with_editor(message, self);
             }
             EditorMessages::RerenderView => return true,
         }
@@ -612,6 +1511,52 @@ where
     }
 }
 
+/// Returns the exporters every editor has.
+fn default_exporters<Data>() -> ExportRegistry<Data>
+where
+    Data: Default + 'static,
+{
+    let mut exporters = ExportRegistry::default();
+    exporters.register(
+        ExporterInfo {
+            name: PNG_EXPORTER,
+            label: "PNG image",
+            mime_type: "image/png",
+            extension: "png",
+        },
+        Rc::new(|editor: &App<Data>, options: &ExportOptions| editor.export_png(options)),
+    );
+
+    exporters
+}
+
+fn files(list: Option<FileList>) -> Vec<File> {
+    match list {
+        Some(list) => (0..list.length()).filter_map(|i| list.get(i)).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Creates a canvas that is not part of the document.
+fn create_canvas(width: u32, height: u32) -> Option<(HtmlCanvasElement, CanvasRenderingContext2d)> {
+    let canvas = web_sys::window()?
+        .document()?
+        .create_element("canvas")
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+
+    Some((canvas, context))
+}
+
 fn enabled_plugins<Data>(plugins: &Plugins<Data>) -> PluginsVec<Data>
 where
     Data: Default + 'static,
@@ -673,10 +1618,15 @@ where
         });
         */
 
-        for (_, plugin) in enabled_plugins(&mut self.plugins) {
+        let plugins = enabled_plugins(&mut self.plugins);
+        for plugin in plugins.values() {
             plugin.as_ref().borrow_mut().render(context, self);
         }
 
+        for plugin in plugins.values() {
+            plugin.as_ref().borrow().render_overlay(context, self);
+        }
+
         let handle = {
             let link = link.clone();
             request_animation_frame(move |time| link.send_message(EditorMessages::Render(time)))
@@ -735,3 +1685,183 @@ where
         app_handle: yew::Renderer::<App<Data>>::with_root(parent).render(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use geo::Coordinate;
+
+    use super::{App, EditorError};
+    use crate::actions::{Action, Redo, Undo};
+    use crate::gizmo::{test_element::Element, Gizmo, MoveGizmo, SetPosition};
+    use crate::snapshot::SnapshotRecorder;
+
+    struct Add(i32);
+
+    impl fmt::Display for Add {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Add {}", self.0)
+        }
+    }
+
+    impl Undo<i32> for Add {
+        fn undo(&mut self, map: &mut i32) {
+            *map -= self.0;
+        }
+    }
+
+    impl Redo<i32> for Add {
+        fn redo(&mut self, map: &mut i32) {
+            *map += self.0;
+        }
+    }
+
+    impl Action<i32> for Add {}
+
+    #[test]
+    fn commit_transaction_adds_one_step() {
+        let mut app = App::<i32>::default();
+
+        app.begin_transaction().unwrap();
+        app.push_action(Add(1)).unwrap();
+        app.push_action(Add(2)).unwrap();
+        app.commit_transaction().unwrap();
+
+        assert_eq!(*app.data(), 3);
+        assert_eq!(app.history().position(), 1);
+
+        app.undo();
+        assert_eq!(*app.data(), 0);
+    }
+
+    #[test]
+    fn rollback_transaction() {
+        let mut app = App::<i32>::default();
+
+        app.begin_transaction().unwrap();
+        app.push_action(Add(1)).unwrap();
+        app.rollback_transaction().unwrap();
+
+        assert_eq!(*app.data(), 0);
+        assert_eq!(app.history().position(), 0);
+    }
+
+    #[test]
+    fn failing_transaction_is_rolled_back() {
+        let mut app = App::<i32>::default();
+
+        let result = app.transaction(|app| {
+            app.push_action(Add(1))?;
+            app.begin_transaction()
+        });
+
+        assert!(matches!(result, Err(EditorError::TransactionActive)));
+        assert!(!app.has_active_transaction());
+        assert_eq!(*app.data(), 0);
+    }
+
+    #[test]
+    fn push_action_without_transaction() {
+        let mut app = App::<i32>::default();
+
+        assert!(matches!(
+            app.push_action(Add(1)),
+            Err(EditorError::NoActiveTransaction)
+        ));
+        assert_eq!(*app.data(), 0);
+    }
+
+    #[test]
+    fn recorder_records_direct_changes() {
+        let mut app = App::<i32>::default();
+        app.set_recorder(SnapshotRecorder::default());
+
+        app.transaction(|app| {
+            *app.data_mut() = 5;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(app.history().position(), 1);
+
+        app.begin_transaction().unwrap();
+        *app.data_mut() = 7;
+        app.rollback_transaction().unwrap();
+        assert_eq!(*app.data(), 5);
+        assert_eq!(app.history().position(), 1);
+
+        assert!(app.undo());
+        assert_eq!(*app.data(), 0);
+    }
+
+    #[test]
+    fn gestures_are_recorded_as_one_step() {
+        let mut app = App::<i32>::default();
+        app.set_recorder(SnapshotRecorder::default());
+
+        // A click that does not change the data adds nothing
+        app.begin_gesture();
+        app.end_gesture();
+        assert!(!app.is_recording());
+        assert_eq!(app.history().position(), 0);
+
+        app.begin_gesture();
+        *app.data_mut() = 3;
+        *app.data_mut() = 4;
+        assert!(!app.undo());
+
+        // Released outside of the canvas, ended by the window
+        app.end_gesture();
+        assert_eq!(app.history().position(), 1);
+        assert!(app.undo());
+        assert_eq!(*app.data(), 0);
+    }
+
+    #[test]
+    fn finished_gizmo_drags_are_added_to_the_history() {
+        let start = Coordinate { x: 10., y: 20. };
+        let end = Coordinate { x: 50., y: 30. };
+
+        let mut app = App::<Vec<Element>>::default();
+        app.set_data(vec![Element::at(start)]);
+
+        let mut gizmo = MoveGizmo::new();
+        gizmo.set_position(start);
+        gizmo.mouse_down(start, 0, app.data_mut().iter_mut());
+        Gizmo::<Element>::mouse_move(&mut gizmo, end, app.data_mut().iter_mut());
+
+        assert!(app.finish_interaction(|data| gizmo.mouse_up(end, 0, data.iter_mut())));
+        assert_eq!(app.history().position(), 1);
+
+        assert!(app.undo());
+        assert_eq!(app.data()[0].position, start);
+        assert!(app.redo());
+        assert_eq!(app.data()[0].position, end);
+    }
+
+    #[test]
+    fn rollback_during_gesture_reverts_direct_changes() {
+        let mut app = App::<i32>::default();
+        app.set_recorder(SnapshotRecorder::default());
+
+        app.begin_gesture();
+        *app.data_mut() = 1;
+
+        let result = app.transaction(|app| {
+            *app.data_mut() = 5;
+            app.push_action(Add(2))?;
+            app.begin_transaction()
+        });
+        assert!(result.is_err());
+        assert_eq!(*app.data(), 1);
+
+        app.transaction(|app| app.push_action(Add(2))).unwrap();
+        assert_eq!(*app.data(), 3);
+
+        // The gesture is still one step, including the committed transaction
+        app.end_gesture();
+        assert_eq!(app.history().position(), 1);
+        assert!(app.undo());
+        assert_eq!(*app.data(), 0);
+    }
+}